@@ -29,9 +29,9 @@ fn main () {
 
 fn solve (clues: Clues) -> Clues {
 
-	let grid = Grid::new (clues.num_rows (), clues.num_cols ());
+	let grid = Grid::with_colours (clues.num_rows (), clues.num_cols (), clues.num_colours ());
 
-	let mut grid_solver = GridSolver::new (grid, clues);
+	let mut grid_solver = GridSolver::new (grid, clues).unwrap ();
 
 	while let Some (_event) = grid_solver.next () {
 		//println! ("{:?}", event);
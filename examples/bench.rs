@@ -0,0 +1,116 @@
+#![ allow (unused_parens) ]
+#![ allow (clippy::redundant_field_names) ]
+
+use std::env;
+use std::path::Path;
+use std::time;
+
+use nono::*;
+
+fn main () {
+
+	// parse args
+
+	let names: Vec <String> = env::args ().skip (1).collect ();
+
+	if names.is_empty () {
+		println! ("Syntax: nono-bench FILE...");
+		return;
+	}
+
+	// solve each puzzle with and without packed lines
+
+	let packed_thresholds = [
+		("unpacked", LineSize::MAX),
+		("packed", DEFAULT_PACKED_THRESHOLD),
+		("all packed", 0),
+	];
+
+	println! (
+		"{:30}{}",
+		"puzzle",
+		packed_thresholds.iter ().map (
+			|(name, _)| format! ("{:>14}", name),
+		).collect::<String> (),
+	);
+
+	for name in names.iter () {
+
+		let clues = Clues::load_file (
+			Path::new (name),
+		).unwrap ();
+
+		let timings = packed_thresholds.iter ().map (
+			|& (_, packed_threshold)| solve (& clues, packed_threshold),
+		).collect::<Vec <String>> ();
+
+		println! (
+			"{:30}{}",
+			name,
+			timings.iter ().map (
+				|value| format! ("{:>14}", value),
+			).collect::<String> (),
+		);
+
+	}
+
+}
+
+// report the fastest of several runs, to reduce noise
+
+const RUNS: usize = 5;
+
+fn solve (
+	clues: & Clues,
+	packed_threshold: LineSize,
+) -> String {
+
+	let mut fastest: Option <time::Duration> = None;
+
+	for _ in 0 .. RUNS {
+
+		let elapsed = match solve_once (clues, packed_threshold) {
+			Some (val) => val,
+			None => return "unsolved".to_string (),
+		};
+
+		fastest = Some (fastest.map_or (elapsed, |fastest| fastest.min (elapsed)));
+
+	}
+
+	format! ("{:.2}ms", fastest.unwrap ().as_secs_f64 () * 1000.0)
+
+}
+
+fn solve_once (
+	clues: & Clues,
+	packed_threshold: LineSize,
+) -> Option <time::Duration> {
+
+	let start = time::Instant::now ();
+
+	let grid = Grid::with_colours (
+		clues.num_rows (),
+		clues.num_cols (),
+		clues.num_colours (),
+	);
+
+	let mut grid_solver = GridSolver::with_line_solver (
+		grid,
+		clues.clone (),
+		GridSolverConfig {
+			line_cache_size: 0,
+			.. Default::default ()
+		},
+		LineSolver::with_packed_threshold (packed_threshold),
+	).unwrap ();
+
+	while grid_solver.next ().is_some () { }
+
+	if grid_solver.is_solved () {
+		Some (start.elapsed ())
+	} else {
+		None
+	}
+
+}
@@ -12,20 +12,88 @@ fn main () {
 
 	// parse args
 
-	let args: Vec <String> = env::args ().collect ();
+	let mut config = GridSolverConfig::default ();
+	let mut fast = false;
+	let mut rate_only = false;
+	let mut check_only = false;
+	let mut options = Options::default ();
+	let mut resume: Option <String> = None;
+	let mut grid_name: Option <String> = None;
+	let mut names: Vec <String> = Vec::new ();
+
+	for arg in env::args ().skip (1) {
+		match arg.as_str () {
+			"--backtrack" => config.backtracking = true,
+			"--probe" => config.probing = true,
+			"--fast" => fast = true,
+			"--rate" => rate_only = true,
+			"--check" => check_only = true,
+			"--dot" => options.dot = true,
+			_ if arg.starts_with ("--checkpoint=") =>
+				options.checkpoint = Some (arg ["--checkpoint=".len () .. ].to_string ()),
+			_ if arg.starts_with ("--grid=") =>
+				grid_name = Some (arg ["--grid=".len () .. ].to_string ()),
+			_ if arg.starts_with ("--resume=") =>
+				resume = Some (arg ["--resume=".len () .. ].to_string ()),
+			_ if arg.starts_with ("--explain=") => {
+				options.explain = parse_position (& arg ["--explain=".len () .. ]);
+				if options.explain.is_none () {
+					println! ("Invalid cell position: {}", arg);
+					return;
+				}
+				config.record_proof = true;
+			},
+			"--priority" => config.scan_order = ScanOrder::Priority,
+			"--no-cache" => config.line_cache_size = 0,
+			"--parallel" => config.threads = thread::available_parallelism ()
+				.map (|threads| threads.get ())
+				.unwrap_or (1),
+			_ => names.push (arg),
+		}
+	}
 
-	if args.len () != 2 {
-		println! ("Syntax: nono-cli FILE");
+	if names.len () != if resume.is_some () { 0 } else { 1 }
+	|| (resume.is_some () && grid_name.is_some ())
+	|| (check_only && grid_name.is_none ()) {
+		println! (
+			"Syntax: nono-cli [--backtrack] [--probe] [--fast] [--priority] [--no-cache] \
+			[--parallel] [--rate] [--explain=ROW,COL [--dot]] [--checkpoint=CHECKPOINT] \
+			(FILE [--grid=GRID [--check]] | --resume=CHECKPOINT)",
+		);
 		return;
 	}
 
-	let name = & args [1];
+	// load clues, or clues and solver state from a checkpoint
 
-	// load clues
+	let clues = match resume {
 
-	let clues = Clues::load_file (
-		& Path::new (name),
-	).unwrap ();
+		Some (ref resume) => match load_checkpoint_file (Path::new (resume)) {
+			Ok ((clues, snapshot)) => {
+
+				// flags given now add to the ones the checkpoint was saved with
+
+				config.backtracking |= snapshot.backtracking ();
+				config.probing |= snapshot.probing ();
+
+				options.snapshot = Some (snapshot);
+				clues
+
+			},
+			Err (error) => {
+				println! ("Can't resume from {}: {}", resume, error);
+				return;
+			},
+		},
+
+		None => match Clues::load_file (Path::new (& names [0])) {
+			Ok (clues) => clues,
+			Err (error) => {
+				println! ("Can't load {}: {}", names [0], error);
+				return;
+			},
+		},
+
+	};
 
 	if ! clues.is_consistent () {
 
@@ -39,37 +107,178 @@ fn main () {
 
 	}
 
+	if options.checkpoint.is_some () && clues.is_coloured () {
+		println! ("Checkpoints aren't supported for coloured clues");
+		return;
+	}
+
+	// load the starting grid, if given
+
+	let grid = match grid_name {
+
+		Some (ref grid_name) if clues.is_coloured () => {
+			println! ("Can't load {}: grid files aren't supported for coloured clues", grid_name);
+			return;
+		},
+
+		Some (ref grid_name) => match Grid::load_file (Path::new (grid_name)) {
+			Ok (grid) => grid,
+			Err (error) => {
+				println! ("Can't load grid from {}: {}", grid_name, error);
+				return;
+			},
+		},
+
+		None => Grid::with_colours (
+			clues.num_rows (),
+			clues.num_cols (),
+			clues.num_colours (),
+		),
+
+	};
+
+	if grid.num_rows () != clues.num_rows () || grid.num_cols () != clues.num_cols () {
+
+		println! (
+			"Grid size {}x{} doesn't match clues size {}x{}",
+			grid.num_rows (),
+			grid.num_cols (),
+			clues.num_rows (),
+			clues.num_cols (),
+		);
+
+		return;
+
+	}
+
+	// check the grid is a solution
+
+	if check_only {
+
+		let report = clues.check (& grid);
+
+		if report.is_ok () {
+			println! ("Grid matches the clues");
+		} else {
+			print! ("{}", report);
+		}
+
+		return;
+
+	}
+
+	// rate
+
+	if rate_only {
+
+		match rate (& clues) {
+
+			Some (rating) => println! (
+				"Rating: {}, needs {}, {} lines, {} probes, {} guesses{}",
+				rating.score,
+				rating.technique,
+				rating.line_solves,
+				rating.probes,
+				rating.guesses,
+				if rating.unique { "" } else { ", more than one solution" },
+			),
+
+			None => println! ("No solution"),
+
+		}
+
+		return;
+
+	}
+
 	// solve
 
-	solve (clues);
+	if fast {
+		solve (grid, clues, config, FastLineSolver::new (), options);
+	} else {
+		solve (grid, clues, config, LineSolver::default (), options);
+	}
 
 }
 
-fn solve (
+#[ derive (Default) ]
+struct Options {
+	explain: Option <(LineSize, LineSize)>,
+	dot: bool,
+	checkpoint: Option <String>,
+	snapshot: Option <GridSolverSnapshot>,
+}
+
+// how often to save the checkpoint, if enabled
+
+const CHECKPOINT_INTERVAL: time::Duration = time::Duration::from_secs (10);
+
+fn solve <Solver: LineSolve + Default + Send> (
+	grid: Grid,
 	clues: Clues,
+	config: GridSolverConfig,
+	line_solver: Solver,
+	options: Options,
 ) {
 
-	let grid = Grid::new (
-		clues.num_rows (),
-		clues.num_cols (),
-	);
-
 	let mut grid_printer = GridPrinter::new (& clues);
 
+	let mut grid_solver = match GridSolver::with_line_solver (
+		grid,
+		clues,
+		config,
+		line_solver,
+	) {
+		Ok (grid_solver) => grid_solver,
+		Err (error) => {
+			println! ("Can't solve the grid: {}", error);
+			return;
+		},
+	};
+
+	if let Some (snapshot) = options.snapshot {
+		grid_solver.restore (snapshot);
+	}
+
 	grid_printer.print (
 		& mut io::stdout ().lock (),
-		& grid,
+		grid_solver.grid (),
 	).unwrap ();
 
-	let mut grid_solver = GridSolver::new (
-		grid,
-		clues,
-	);
+	let mut last_checkpoint = time::Instant::now ();
 
 	while let Some (event) = grid_solver.next () {
 
+		// save progress between lines, probes and guesses, so little work is
+		// lost
+
+		if let (
+			Some (ref checkpoint),
+			GridSolverEvent::StartRow (_)
+				| GridSolverEvent::StartCol (_)
+				| GridSolverEvent::Probe (..)
+				| GridSolverEvent::Guess (..)
+				| GridSolverEvent::Backtrack (..),
+		) = (& options.checkpoint, & event) {
+
+			if last_checkpoint.elapsed () >= CHECKPOINT_INTERVAL {
+
+				save_checkpoint_file (
+					Path::new (checkpoint),
+					grid_solver.clues (),
+					& grid_solver.snapshot (),
+				).unwrap ();
+
+				last_checkpoint = time::Instant::now ();
+
+			}
+
+		}
+
 		let mut redraw = false;
 
+		grid_printer.set_highlight (None);
+
 		match event {
 
 			GridSolverEvent::SolvedCell { .. } => {
@@ -84,10 +293,58 @@ fn solve (
 				print! ("\r\x1b[2Kcol {} ...", index);
 			},
 
-			GridSolverEvent::SolvedGrid { .. } => {
+			GridSolverEvent::SolvedGrid => {
+				redraw = true;
+			},
+
+			GridSolverEvent::Probe (row_index, col_index) => {
+				print! ("\r\x1b[2Kprobe {} {} ...", row_index, col_index);
+				grid_printer.set_highlight (Some ((row_index, col_index)));
+				redraw = true;
+			},
+
+			GridSolverEvent::ProbedCell { .. } => {
+				redraw = true;
+			},
+
+			GridSolverEvent::Guess (row_index, col_index, _) => {
+				print! ("\r\x1b[2Kguess {} {} ...", row_index, col_index);
 				redraw = true;
 			},
 
+			GridSolverEvent::Backtrack (row_index, col_index, _) => {
+				print! ("\r\x1b[2Kbacktrack {} {} ...", row_index, col_index);
+				redraw = true;
+			},
+
+			GridSolverEvent::Contradiction (line_ref, clues_line) => {
+
+				println! (
+					"\r\x1b[2KNo solution: contradiction in {} with clues {:?}",
+					line_ref,
+					clues_line,
+				);
+
+				return;
+
+			},
+
+			GridSolverEvent::Stuck => {
+
+				grid_printer.print (
+					& mut io::stdout ().lock (),
+					grid_solver.grid (),
+				).unwrap ();
+
+				println! (
+					"\r\x1b[2KStuck: line solving can't make further progress \
+					(try --probe or --backtrack)",
+				);
+
+				return;
+
+			},
+
 			_ => (),
 
 		};
@@ -96,7 +353,7 @@ fn solve (
 
 			grid_printer.print (
 				& mut io::stdout ().lock (),
-				& grid_solver.grid (),
+				grid_solver.grid (),
 			).unwrap ();
 
 			thread::sleep (time::Duration::from_millis (20));
@@ -106,10 +363,42 @@ fn solve (
 	}
 
 	println! (
-		"\r\x1b[2KSolved in {} iterations, {} lines",
+		"\r\x1b[2KSolved in {} iterations, {} lines, {} guesses",
 		grid_solver.stats ().grid_iterations,
 		grid_solver.stats ().line_iterations,
+		grid_solver.stats ().guesses,
 	);
 
+	// explain a cell
+
+	if let Some ((row_index, col_index)) = options.explain {
+
+		if row_index >= grid_solver.grid ().num_rows ()
+		|| col_index >= grid_solver.grid ().num_cols () {
+			println! ("Cell {},{} is outside the grid", row_index, col_index);
+			return;
+		}
+
+		let proof = grid_solver.proof ().unwrap ();
+
+		if options.dot {
+			print! ("{}", proof.to_dot (grid_solver.grid (), row_index, col_index));
+		} else {
+			print! ("{}", proof.explain (grid_solver.grid (), row_index, col_index));
+		}
+
+	}
+
+}
+
+fn parse_position (source: & str) -> Option <(LineSize, LineSize)> {
+
+	let (row_index, col_index) = source.split_once (',') ?;
+
+	Some ((
+		row_index.trim ().parse ().ok () ?,
+		col_index.trim ().parse ().ok () ?,
+	))
+
 }
 
@@ -26,6 +26,19 @@ const CLUE_GAP: f64 = 2.0;
 
 fn main () {
 
+	// take our own flags out of the args, and leave the rest to gtk
+
+	let mut config: GridSolverConfig = Default::default ();
+	let mut args: Vec <String> = Vec::new ();
+
+	for arg in env::args () {
+		match arg.as_str () {
+			"--backtrack" => config.backtracking = true,
+			"--probe" => config.probing = true,
+			_ => args.push (arg),
+		}
+	}
+
 	// create gtk app
 
 	let application =
@@ -33,12 +46,11 @@ fn main () {
 			Some ("com.jamespharaoh.nono"),
 			gio::ApplicationFlags::HANDLES_OPEN);
 
-	application.connect_open (|app, files, hint| {
-		handle_open (app, files, hint);
+	application.connect_open (move |app, files, hint| {
+		handle_open (app, files, hint, & config);
 	});
 
-	application.run_with_args (
-		& env::args ().collect::<Vec <_>> ());
+	application.run_with_args (& args);
 
 }
 
@@ -46,15 +58,17 @@ fn handle_open (
 	application: & gtk::Application,
 	files: & [gio::File],
 	_hint: & str,
+	config: & GridSolverConfig,
 ) {
 	for file in files {
-		handle_open_one (application, file);
+		handle_open_one (application, file, config);
 	}
 }
 
 fn handle_open_one (
 	application: & gtk::Application,
 	file: & gio::File,
+	config: & GridSolverConfig,
 ) {
 
 	// load clues
@@ -66,7 +80,13 @@ fn handle_open_one (
 		input_stream: file_input_stream.upcast (),
 	};
 
-	let clues = Clues::load (& mut reader).unwrap ();
+	let clues = match Clues::load (& mut reader) {
+		Ok (clues) => clues,
+		Err (error) => {
+			println! ("Can't load {}: {}", file.parse_name (), error);
+			return;
+		},
+	};
 
 	if ! clues.is_consistent () {
 
@@ -83,6 +103,7 @@ fn handle_open_one (
 	SolverWindow::new (
 		application,
 		clues,
+		config.clone (),
 	);
 
 }
@@ -94,6 +115,7 @@ struct SolverWindow {
 
 struct SolverWindowState {
 	solver: GridSolver,
+	highlight: Option <(LineSize, LineSize)>,
 	palette: Palette,
 	dimensions: SolverWindowDimensions,
 	window: Option <gtk::ApplicationWindow>,
@@ -113,17 +135,22 @@ impl SolverWindow {
 	pub fn new (
 		application: & gtk::Application,
 		clues: Clues,
+		config: GridSolverConfig,
 	) -> SolverWindow {
 
-		let solver = GridSolver::new (
-			Grid::new (clues.num_rows (), clues.num_cols ()),
+		let palette = Palette::new (& clues);
+
+		let solver = GridSolver::with_config (
+			Grid::with_colours (clues.num_rows (), clues.num_cols (), clues.num_colours ()),
 			clues,
-		);
+			config,
+		).unwrap ();
 
 		let solver_window = SolverWindow {
 			state: Rc::new (RefCell::new (SolverWindowState {
 				solver: solver,
-				palette: Palette::new (),
+				highlight: None,
+				palette: palette,
 				dimensions: Default::default (),
 				window: None,
 				timeout_source: None,
@@ -285,11 +312,17 @@ impl SolverWindow {
 
 		let mut state = self.state.borrow_mut ();
 
-		let _event = match state.solver.next () {
+		let event = match state.solver.next () {
 			Some (val) => val,
 			None => return false,
 		};
 
+		state.highlight = match event {
+			GridSolverEvent::Probe (row_index, col_index) =>
+				Some ((row_index, col_index)),
+			_ => None,
+		};
+
 		true
 
 	}
@@ -375,7 +408,9 @@ impl SolverWindow {
 
 		for (row_index, row_clues) in clues.rows ().enumerate () {
 
-			for (clue_index, clue) in row_clues.iter ().rev ().enumerate () {
+			let row_colours = clues.row_colours (row_index as LineSize);
+
+			for (clue_index, (clue, & colour)) in row_clues.iter ().zip (row_colours).rev ().enumerate () {
 
 				let text = format! ("{}", clue);
 				let text_extents = context.text_extents (& text).unwrap ();
@@ -405,7 +440,7 @@ impl SolverWindow {
 					(CELL_SIZE + text_extents.height ()) / 2.0,
 				);
 
-				context.set_source (& palette.clue_text);
+				context.set_source (& palette.clue_text [colour as usize - 1]);
 				context.show_text (& text);
 
 			}
@@ -451,7 +486,9 @@ impl SolverWindow {
 
 		for (col_index, col_clues) in clues.cols ().enumerate () {
 
-			for (clue_index, clue) in col_clues.iter ().rev ().enumerate () {
+			let col_colours = clues.col_colours (col_index as LineSize);
+
+			for (clue_index, (clue, & colour)) in col_clues.iter ().zip (col_colours).rev ().enumerate () {
 
 				let text = format! ("{}", clue);
 				let text_extents = context.text_extents (& text).unwrap ();
@@ -481,7 +518,7 @@ impl SolverWindow {
 					- (CELL_SIZE - text_extents.height ()) / 2.0,
 				);
 
-				context.set_source (& palette.clue_text);
+				context.set_source (& palette.clue_text [colour as usize - 1]);
 				context.show_text (& text);
 
 			}
@@ -520,12 +557,19 @@ impl SolverWindow {
 		for row_index in 0 .. grid.num_rows () {
 			for col_index in 0 .. grid.num_cols () {
 
+				let highlight =
+					state.highlight == Some ((row_index, col_index));
+
 				context.set_source (
 					match grid [(row_index, col_index)] {
+						_ if highlight => & palette.highlight,
 						Cell::UNKNOWN => & palette.unknown,
 						Cell::EMPTY   => & palette.empty,
-						Cell::FILLED  => & palette.filled,
-						_             => & palette.error,
+						cell          => match cell.colour_index () {
+							Some (index) => & palette.filled [index as usize - 1],
+							None if cell.is_error () => & palette.error,
+							None => & palette.unknown,
+						},
 					},
 				);
 
@@ -696,27 +740,52 @@ impl io::Read for InputStreamReader {
 struct Palette {
 	background: cairo::SolidPattern,
 	lines: cairo::SolidPattern,
-	clue_text: cairo::SolidPattern,
+	clue_text: Vec <cairo::SolidPattern>,
 	clue_box: cairo::SolidPattern,
 	unknown: cairo::SolidPattern,
-	filled: cairo::SolidPattern,
+	filled: Vec <cairo::SolidPattern>,
 	empty: cairo::SolidPattern,
 	error: cairo::SolidPattern,
+	highlight: cairo::SolidPattern,
 }
 
 impl Palette {
 
-	fn new () -> Palette {
+	fn new (clues: & Clues) -> Palette {
+
+		// coloured clues are drawn in their own colours, as are their cells
+
+		let (clue_text, filled) = if clues.is_coloured () {
+
+			let colours: Vec <cairo::SolidPattern> = clues.palette ().iter ().map (
+				|colour| Self::from_rgb (
+					colour.red as f64 / 255.0,
+					colour.green as f64 / 255.0,
+					colour.blue as f64 / 255.0,
+				),
+			).collect ();
+
+			(colours.clone (), colours)
+
+		} else {
+
+			(
+				vec! [ Self::from_rgb (0.00, 0.00, 0.00) ],
+				vec! [ Self::from_rgb (0.10, 0.10, 0.10) ],
+			)
+
+		};
 
 		Palette {
 			background: Self::from_rgb (0.85, 0.85, 0.85),
 			lines:      Self::from_rgb (0.00, 0.00, 0.00),
-			clue_text:  Self::from_rgb (0.00, 0.00, 0.00),
+			clue_text:  clue_text,
 			clue_box:   Self::from_rgb (0.85, 0.85, 0.85),
 			unknown:    Self::from_rgb (0.70, 0.70, 0.70),
-			filled:     Self::from_rgb (0.10, 0.10, 0.10),
+			filled:     filled,
 			empty:      Self::from_rgb (1.00, 1.00, 1.00),
 			error:      Self::from_rgb (0.80, 0.20, 0.20),
+			highlight:  Self::from_rgb (0.30, 0.50, 0.90),
 		}
 
 	}
@@ -0,0 +1,85 @@
+#![ allow (unused_parens) ]
+#![ allow (clippy::redundant_field_names) ]
+
+use std::env;
+use std::path::Path;
+
+use nono::*;
+
+fn main () {
+
+	// parse args
+
+	let names: Vec <String> = env::args ().skip (1).collect ();
+
+	if names.is_empty () {
+		println! ("Syntax: nono-strategies FILE...");
+		return;
+	}
+
+	// solve each puzzle with each scan order
+
+	let scan_orders = [
+		ScanOrder::RoundRobin,
+		ScanOrder::Priority,
+	];
+
+	println! (
+		"{:30}{}",
+		"puzzle",
+		scan_orders.iter ().map (
+			|scan_order| format! ("{:>12}", format! ("{:?}", scan_order)),
+		).collect::<String> (),
+	);
+
+	for name in names.iter () {
+
+		let clues = Clues::load_file (
+			Path::new (name),
+		).unwrap ();
+
+		let line_iterations = scan_orders.iter ().map (
+			|& scan_order| solve (& clues, scan_order),
+		).collect::<Vec <String>> ();
+
+		println! (
+			"{:30}{}",
+			name,
+			line_iterations.iter ().map (
+				|value| format! ("{:>12}", value),
+			).collect::<String> (),
+		);
+
+	}
+
+}
+
+fn solve (
+	clues: & Clues,
+	scan_order: ScanOrder,
+) -> String {
+
+	let grid = Grid::with_colours (
+		clues.num_rows (),
+		clues.num_cols (),
+		clues.num_colours (),
+	);
+
+	let mut grid_solver = GridSolver::with_config (
+		grid,
+		clues.clone (),
+		GridSolverConfig {
+			scan_order: scan_order,
+			.. Default::default ()
+		},
+	).unwrap ();
+
+	while grid_solver.next ().is_some () { }
+
+	if grid_solver.is_solved () {
+		format! ("{}", grid_solver.stats ().line_iterations)
+	} else {
+		"unsolved".to_string ()
+	}
+
+}
@@ -3,7 +3,12 @@ const EMPTY: u8   = 0b01;
 const FILLED: u8  = 0b10;
 const UNKNOWN: u8 = 0b11;
 
-#[ derive (Clone, Copy, Debug, Eq, PartialEq) ]
+// colour puzzles use one more bit per colour, with colour one sharing the bit
+// used for filled cells, so a cell holds every colour it could still be
+
+pub const MAX_COLOURS: u8 = 7;
+
+#[ derive (Clone, Copy, Debug, Eq, Hash, PartialEq) ]
 #[ repr (transparent) ]
 pub struct Cell {
 	bits: u8,
@@ -28,16 +33,15 @@ impl Cell {
 		self.bits == FILLED
 	}
 
+	// anything which could still be more than one thing, so in colour puzzles
+	// this includes cells which have been narrowed down to some of the colours
+
 	pub fn is_unknown (self) -> bool {
-		self.bits == UNKNOWN
+		self.bits.count_ones () > 1
 	}
 
 	pub fn is_solved (self) -> bool {
-		match self.bits {
-			EMPTY => true,
-			FILLED => true,
-			_ => false,
-		}
+		self.bits.count_ones () == 1
 	}
 
 	pub fn can_empty (self) -> bool {
@@ -48,5 +52,39 @@ impl Cell {
 		self.bits & FILLED != ERROR
 	}
 
+	pub fn colour (index: u8) -> Cell {
+		assert! ((1 ..= MAX_COLOURS).contains (& index));
+		Cell { bits: 1 << index }
+	}
+
+	pub fn unknown (num_colours: u8) -> Cell {
+		assert! ((1 ..= MAX_COLOURS).contains (& num_colours));
+		Cell { bits: (2 << num_colours) - 1 }
+	}
+
+	pub fn can_colour (self, index: u8) -> bool {
+		self.bits & (1 << index) != ERROR
+	}
+
+	pub fn colour_index (self) -> Option <u8> {
+		if self.is_solved () && self.bits != EMPTY {
+			Some (self.bits.trailing_zeros () as u8)
+		} else {
+			None
+		}
+	}
+
+	pub fn union (self, other: Cell) -> Cell {
+		Cell { bits: self.bits | other.bits }
+	}
+
+	pub fn intersect (self, other: Cell) -> Cell {
+		Cell { bits: self.bits & other.bits }
+	}
+
+	pub fn without (self, other: Cell) -> Cell {
+		Cell { bits: self.bits & ! other.bits }
+	}
+
 }
 
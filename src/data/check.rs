@@ -0,0 +1,220 @@
+use std::fmt;
+
+use crate::data::*;
+use crate::solver::LineRef;
+
+// the lines of a grid which don't match their clues, with the runs which were
+// actually found in each of them
+
+#[ derive (Clone, Debug, Eq, PartialEq) ]
+pub struct CheckReport {
+	pub size_mismatch: Option <SizeMismatch>,
+	pub mismatches: Vec <LineMismatch>,
+	palette: Vec <Colour>,
+}
+
+// a grid of the wrong size can't be compared line by line, so this is reported
+// on its own instead
+
+#[ derive (Clone, Copy, Debug, Eq, PartialEq) ]
+pub struct SizeMismatch {
+	pub expected: (LineSize, LineSize),
+	pub actual: (LineSize, LineSize),
+}
+
+#[ derive (Clone, Debug, Eq, PartialEq) ]
+pub struct LineMismatch {
+	pub line_ref: LineRef,
+	pub expected: CluesLine,
+	pub expected_colours: Vec <u8>,
+	pub actual: CluesLine,
+	pub actual_colours: Vec <u8>,
+	pub unsolved: LineSize,
+}
+
+impl Clues {
+
+	pub fn check (& self, grid: & Grid) -> CheckReport {
+
+		let expected = (self.num_rows (), self.num_cols ());
+		let actual = (grid.num_rows (), grid.num_cols ());
+
+		if actual != expected {
+			return CheckReport {
+				size_mismatch: Some (SizeMismatch {
+					expected: expected,
+					actual: actual,
+				}),
+				mismatches: Vec::new (),
+				palette: self.palette ().to_vec (),
+			};
+		}
+
+		let rows = (0 .. self.num_rows ()).map (
+			|index| (LineRef::Row (index), self.row (index), self.row_colours (index), grid.row (index).collect ()),
+		);
+
+		let cols = (0 .. self.num_cols ()).map (
+			|index| (LineRef::Col (index), self.col (index), self.col_colours (index), grid.col (index).collect ()),
+		);
+
+		let mismatches = rows.chain (cols).filter_map (
+			|(line_ref, clues_line, colours, line): (LineRef, & CluesLine, & [u8], LineBuf)| {
+
+				let (actual, actual_colours) = line_runs (& line);
+
+				let unsolved = line.iter ().filter (
+					|cell| ! cell.is_solved (),
+				).count () as LineSize;
+
+				if unsolved == 0 && & actual == clues_line && actual_colours == colours {
+					return None;
+				}
+
+				Some (LineMismatch {
+					line_ref: line_ref,
+					expected: clues_line.clone (),
+					expected_colours: colours.to_vec (),
+					actual: actual,
+					actual_colours: actual_colours,
+					unsolved: unsolved,
+				})
+
+			},
+		).collect ();
+
+		CheckReport {
+			size_mismatch: None,
+			mismatches: mismatches,
+			palette: self.palette ().to_vec (),
+		}
+
+	}
+
+}
+
+impl CheckReport {
+
+	pub fn is_ok (& self) -> bool {
+		self.size_mismatch.is_none () && self.mismatches.is_empty ()
+	}
+
+	fn format_runs (& self, runs: & CluesLine, colours: & [u8]) -> String {
+
+		if runs.is_empty () {
+			return "nothing".to_string ();
+		}
+
+		runs.iter ().zip (colours).map (
+			|(run, & colour)| match self.palette.get (colour as usize - 1) {
+				Some (colour) => format! ("{}{}", run, colour.name),
+				None => format! ("{}", run),
+			},
+		).collect::<Vec <String>> ().join (" ")
+
+	}
+
+}
+
+impl fmt::Display for CheckReport {
+
+	fn fmt (& self, formatter: & mut fmt::Formatter <'_>) -> fmt::Result {
+
+		if let Some (size_mismatch) = self.size_mismatch {
+			writeln! (
+				formatter,
+				"size: expected {}x{}, found {}x{}",
+				size_mismatch.expected.0,
+				size_mismatch.expected.1,
+				size_mismatch.actual.0,
+				size_mismatch.actual.1,
+			) ?;
+		}
+
+		for mismatch in self.mismatches.iter () {
+
+			write! (
+				formatter,
+				"{}: expected {}, found {}",
+				mismatch.line_ref,
+				self.format_runs (& mismatch.expected, & mismatch.expected_colours),
+				self.format_runs (& mismatch.actual, & mismatch.actual_colours),
+			) ?;
+
+			if mismatch.unsolved > 0 {
+				write! (formatter, " with {} unsolved cells", mismatch.unsolved) ?;
+			}
+
+			writeln! (formatter) ?;
+
+		}
+
+		Ok (())
+
+	}
+
+}
+
+#[ cfg (test) ]
+mod tests {
+
+	use super::*;
+	use crate::solver::load_clues;
+
+	#[ test ]
+	fn test_check () {
+
+		let clues = load_clues ("rows\n1 1\n2\ncols\n1\n1\n2\n");
+
+		let solution = Grid::load (& mut "# #\n ##\n".as_bytes ()).unwrap ();
+		assert! (clues.check (& solution).is_ok ());
+
+		let wrong = Grid::load (& mut "## \n -#\n".as_bytes ()).unwrap ();
+		let report = clues.check (& wrong);
+
+		assert_eq! (report.mismatches.len (), 4);
+		assert_eq! (report.mismatches [0].actual, vec! [ 2 ]);
+
+		assert_eq! (
+			report.to_string (),
+			"row 0: expected 1 1, found 2\n\
+			row 1: expected 2, found 1 with 1 unsolved cells\n\
+			col 1: expected 1, found 1 with 1 unsolved cells\n\
+			col 2: expected 2, found 1\n",
+		);
+
+	}
+
+	#[ test ]
+	fn test_check_size () {
+
+		let clues = load_clues ("rows\n1 1\n2\ncols\n1\n1\n2\n");
+
+		let grid = Grid::load (& mut "# \n #\n".as_bytes ()).unwrap ();
+		let report = clues.check (& grid);
+
+		assert! (! report.is_ok ());
+		assert! (report.mismatches.is_empty ());
+		assert_eq! (report.to_string (), "size: expected 2x3, found 2x2\n");
+
+	}
+
+	#[ test ]
+	fn test_check_colours () {
+
+		let clues = load_clues ("colours\nr ff0000\nb 0000ff\nrows\n1r 1b\ncols\n1r\n1b\n");
+
+		let mut grid = Grid::with_colours (1, 2, 2);
+		grid [(0, 0)] = Cell::colour (2);
+		grid [(0, 1)] = Cell::colour (1);
+
+		assert_eq! (
+			clues.check (& grid).to_string (),
+			"row 0: expected 1r 1b, found 1b 1r\n\
+			col 0: expected 1r, found 1b\n\
+			col 1: expected 1b, found 1r\n",
+		);
+
+	}
+
+}
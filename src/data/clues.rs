@@ -1,26 +1,154 @@
-use std::error::Error;
 use std::io;
 use std::io::BufRead;
 use std::io::BufReader;
 use std::fs::File;
-use std::num;
 use std::path::Path;
 
 use crate::data::*;
+use crate::solver::LineRef;
 
 pub type CluesLine = Vec <LineSize>;
 
-#[ derive (Debug) ]
+// names and colours for grids which don't come with a palette
+
+const DEFAULT_PALETTE: [(& str, u8, u8, u8); MAX_COLOURS as usize] = [
+	("r", 0xff, 0x00, 0x00),
+	("g", 0x00, 0xa0, 0x00),
+	("b", 0x00, 0x00, 0xff),
+	("y", 0xe0, 0xc0, 0x00),
+	("m", 0xc0, 0x00, 0xc0),
+	("c", 0x00, 0xc0, 0xc0),
+	("k", 0x00, 0x00, 0x00),
+];
+
+// the colour of each clue is held alongside the clues, numbered from one, and
+// is always one for puzzles without a palette
+
+#[ derive (Clone, Debug) ]
 pub struct Clues {
 	rows: Vec <CluesLine>,
 	cols: Vec <CluesLine>,
+	row_colours: Vec <Vec <u8>>,
+	col_colours: Vec <Vec <u8>>,
+	palette: Vec <Colour>,
+}
+
+#[ derive (Clone, Debug, Eq, PartialEq) ]
+pub struct Colour {
+	pub name: String,
+	pub red: u8,
+	pub green: u8,
+	pub blue: u8,
 }
 
 impl Clues {
 
+	pub fn new (
+		rows: Vec <CluesLine>,
+		cols: Vec <CluesLine>,
+	) -> Clues {
+
+		let row_colours = rows.iter ().map (
+			|clues_line| vec! [1; clues_line.len ()],
+		).collect ();
+
+		let col_colours = cols.iter ().map (
+			|clues_line| vec! [1; clues_line.len ()],
+		).collect ();
+
+		Clues {
+			rows: rows,
+			cols: cols,
+			row_colours: row_colours,
+			col_colours: col_colours,
+			palette: Vec::new (),
+		}
+
+	}
+
+	pub fn with_colours (
+		rows: Vec <CluesLine>,
+		cols: Vec <CluesLine>,
+		row_colours: Vec <Vec <u8>>,
+		col_colours: Vec <Vec <u8>>,
+		palette: Vec <Colour>,
+	) -> Result <Clues, CluesError> {
+
+		if palette.len () > MAX_COLOURS as usize {
+			return Err (CluesError::TooManyColours);
+		}
+
+		if let Some (index) = find_colours_mismatch (& rows, & row_colours) {
+			return Err (CluesError::ColoursMismatch (LineRef::Row (index as LineSize)));
+		}
+
+		if let Some (index) = find_colours_mismatch (& cols, & col_colours) {
+			return Err (CluesError::ColoursMismatch (LineRef::Col (index as LineSize)));
+		}
+
+		Ok (Clues {
+			rows: rows,
+			cols: cols,
+			row_colours: row_colours,
+			col_colours: col_colours,
+			palette: palette,
+		})
+
+	}
+
+	// the clues of a solved grid, with a default palette for colour grids
+
+	pub fn from_grid (grid: & Grid) -> Clues {
+
+		let palette = if grid.num_colours () > 1 {
+			DEFAULT_PALETTE [0 .. grid.num_colours () as usize].iter ().map (
+				|& (name, red, green, blue)| Colour {
+					name: name.to_string (),
+					red: red,
+					green: green,
+					blue: blue,
+				},
+			).collect ()
+		} else {
+			Vec::new ()
+		};
+
+		Clues::from_grid_with_palette (grid, palette).unwrap ()
+
+	}
+
+	// the clues of a solved grid, with the palette naming any colours used
+
+	pub fn from_grid_with_palette (
+		grid: & Grid,
+		palette: Vec <Colour>,
+	) -> Result <Clues, CluesError> {
+
+		if ! grid.is_solved () {
+			return Err (CluesError::GridNotSolved);
+		}
+
+		let (rows, row_colours) = (0 .. grid.num_rows ()).map (
+			|index| line_runs (& grid.row (index).collect::<LineBuf> ()),
+		).unzip ();
+
+		let (cols, col_colours): (Vec <CluesLine>, Vec <Vec <u8>>) = (0 .. grid.num_cols ()).map (
+			|index| line_runs (& grid.col (index).collect::<LineBuf> ()),
+		).unzip ();
+
+		let clues = Clues::with_colours (rows, cols, row_colours, col_colours, palette) ?;
+
+		if clues.row_colours.iter ().flatten ().any (|& colour| colour > clues.num_colours ()) {
+			return Err (CluesError::GridColoursMissing);
+		}
+
+		Ok (clues)
+
+	}
+
 	pub fn load_file (
 		filename: & Path,
-	) -> Result <Clues, Box <dyn Error>> {
+	) -> Result <Clues, CluesParseError> {
 
 		let mut file = File::open (
 			filename,
@@ -32,73 +160,160 @@ impl Clues {
 
 	pub fn load (
 		reader: & mut dyn io::Read,
-	) -> Result <Clues, Box <dyn Error>> {
+	) -> Result <Clues, CluesParseError> {
 
 		let reader = BufReader::new (
 			reader,
 		);
 
-		let iter = reader.split (b'\n').map (
-			|result| result.map (
-				|line| String::from_utf8_lossy (& line).to_string (),
-			),
-		).map (
-			|result| result.map (
-				|line| line.trim ().to_string (),
-			),
-		).filter (
-			|result| match result {
-				Ok (line) => line != "",
-				_ => true,
-			}
-		);
-
 		#[ derive (PartialEq) ]
-		enum Mode { None, Rows, Cols }
+		enum Mode { None, Colours, Rows, Cols }
 		let mut mode = Mode::None;
 
 		let mut rows: Vec <Vec <LineSize>> = Vec::new ();
 		let mut cols: Vec <Vec <LineSize>> = Vec::new ();
+		let mut row_colours: Vec <Vec <u8>> = Vec::new ();
+		let mut col_colours: Vec <Vec <u8>> = Vec::new ();
+		let mut palette: Vec <Colour> = Vec::new ();
+
+		let mut line_number = 0;
+
+		for result in reader.split (b'\n') {
+
+			line_number += 1;
+
+			let text = String::from_utf8_lossy (& result ?).to_string ();
+
+			let line = if line_number == 1 {
+				text.strip_prefix ('\u{feff}').unwrap_or (& text)
+			} else {
+				& text
+			};
 
-		for result in iter {
+			let tokens = tokens (line);
 
-			let line = result ?;
+			// tokens have byte offsets, but we report columns in characters
 
-			if line == "rows" {
-				if mode != Mode::None {
-					return Err ("parse error".into ());
+			let error = |offset: usize, kind: CluesParseErrorKind| CluesParseError::Syntax {
+				line: line_number,
+				column: line [ .. offset].chars ().count () + 1,
+				kind: kind,
+			};
+
+			let (first_column, first) = match tokens.first () {
+				Some (& val) => val,
+				None => continue,
+			};
+
+			// section headers are alone on their line
+
+			if [ "colours", "rows", "cols" ].contains (& first) {
+
+				let allowed = match first {
+					"colours" => mode == Mode::None,
+					"rows" => mode == Mode::None || mode == Mode::Colours,
+					_ => mode == Mode::Rows,
+				};
+
+				if ! allowed {
+					return Err (if first == "cols" && mode != Mode::Cols {
+						error (first_column, CluesParseErrorKind::MissingSection ("rows".to_string ()))
+					} else {
+						error (first_column, CluesParseErrorKind::UnexpectedSection (first.to_string ()))
+					});
 				}
-				mode = Mode::Rows;
-				continue;
-			}
 
-			if line == "cols" {
-				if mode != Mode::Rows {
-					return Err ("parse error".into ());
+				if let Some (& (column, text)) = tokens.get (1) {
+					return Err (error (column, CluesParseErrorKind::TrailingData (text.to_string ())));
 				}
-				mode = Mode::Cols;
+
+				mode = match first {
+					"colours" => Mode::Colours,
+					"rows" => Mode::Rows,
+					_ => Mode::Cols,
+				};
+
 				continue;
+
 			}
 
 			if mode == Mode::None {
-				return Err ("parse error".into ());
+				return Err (error (first_column, CluesParseErrorKind::MissingSection ("rows".to_string ())));
+			}
+
+			if mode == Mode::Colours {
+
+				if palette.len () == MAX_COLOURS as usize {
+					return Err (error (first_column, CluesParseErrorKind::TooManyColours));
+				}
+
+				palette.push (parse_colour (& tokens).map_err (
+					|(column, kind)| error (column, kind),
+				) ?);
+
+				continue;
+
 			}
 
-			let clues = line.split (" ").map (
-				|text| text.parse::<LineSize> ()
-			).collect::<Result <Vec <LineSize>, num::ParseIntError>> () ?;
+			// each clue is a number, followed by the name of its colour unless
+			// it is the first one
+
+			let mut clues = Vec::new ();
+			let mut colours = Vec::new ();
+
+			for & (column, text) in tokens.iter () {
+
+				let digits = text.find (|ch: char| ! ch.is_ascii_digit ()).unwrap_or (text.len ());
+				let (size, name) = text.split_at (digits);
+
+				clues.push (size.parse::<LineSize> ().map_err (
+					|_| error (column, CluesParseErrorKind::BadNumber (text.to_string ())),
+				) ?);
+
+				colours.push (if name.is_empty () {
+					1
+				} else if palette.is_empty () {
+					return Err (error (column + digits, CluesParseErrorKind::TrailingData (name.to_string ())));
+				} else {
+					match palette.iter ().position (|colour| colour.name == name) {
+						Some (index) => index as u8 + 1,
+						None => return Err (error (
+							column + digits,
+							CluesParseErrorKind::UnknownColour (name.to_string ()),
+						)),
+					}
+				});
+
+			}
 
 			if mode == Mode::Rows {
 				rows.push (clues);
+				row_colours.push (colours);
 			} else {
 				cols.push (clues);
+				col_colours.push (colours);
 			}
 
 		}
 
+		// a file which ends early is missing the sections after that point
+
+		if mode != Mode::Cols {
+			return Err (CluesParseError::Syntax {
+				line: line_number + 1,
+				column: 1,
+				kind: CluesParseErrorKind::MissingSection (
+					if mode == Mode::Rows { "cols" } else { "rows" }.to_string (),
+				),
+			});
+		}
+
 		Ok (Clues {
 			rows: rows,
 			cols: cols,
+			row_colours: row_colours,
+			col_colours: col_colours,
+			palette: palette,
 		})
 
 	}
@@ -127,6 +342,152 @@ impl Clues {
 		& self.cols [index as usize]
 	}
 
+	pub fn row_colours (& self, index: LineSize) -> & [u8] {
+		& self.row_colours [index as usize]
+	}
+
+	pub fn col_colours (& self, index: LineSize) -> & [u8] {
+		& self.col_colours [index as usize]
+	}
+
+	pub fn palette (& self) -> & [Colour] {
+		& self.palette
+	}
+
+	pub fn is_coloured (& self) -> bool {
+		! self.palette.is_empty ()
+	}
+
+	pub fn num_colours (& self) -> u8 {
+		u8::max (1, self.palette.len () as u8)
+	}
+
+	pub fn builder () -> CluesBuilder {
+		CluesBuilder::new ()
+	}
+
+	// replacing a line keeps the puzzle size, but may leave the clues invalid
+	// until the lines which cross it are changed to match
+
+	pub fn set_row (& mut self, index: LineSize, clues_line: CluesLine) -> Result <(), CluesError> {
+		let colours = vec! [1; clues_line.len ()];
+		self.set_coloured_row (index, clues_line, colours)
+	}
+
+	pub fn set_col (& mut self, index: LineSize, clues_line: CluesLine) -> Result <(), CluesError> {
+		let colours = vec! [1; clues_line.len ()];
+		self.set_coloured_col (index, clues_line, colours)
+	}
+
+	pub fn set_coloured_row (
+		& mut self,
+		index: LineSize,
+		clues_line: CluesLine,
+		colours: Vec <u8>,
+	) -> Result <(), CluesError> {
+
+		let line_ref = LineRef::Row (index);
+
+		if index >= self.num_rows () {
+			return Err (CluesError::LineOutOfRange (line_ref));
+		}
+
+		if clues_line.len () != colours.len () {
+			return Err (CluesError::ColoursMismatch (line_ref));
+		}
+
+		self.rows [index as usize] = clues_line;
+		self.row_colours [index as usize] = colours;
+
+		Ok (())
+
+	}
+
+	pub fn set_coloured_col (
+		& mut self,
+		index: LineSize,
+		clues_line: CluesLine,
+		colours: Vec <u8>,
+	) -> Result <(), CluesError> {
+
+		let line_ref = LineRef::Col (index);
+
+		if index >= self.num_cols () {
+			return Err (CluesError::LineOutOfRange (line_ref));
+		}
+
+		if clues_line.len () != colours.len () {
+			return Err (CluesError::ColoursMismatch (line_ref));
+		}
+
+		self.cols [index as usize] = clues_line;
+		self.col_colours [index as usize] = colours;
+
+		Ok (())
+
+	}
+
+	// checks the clues describe a puzzle which could have a solution, so that
+	// every clue is positive, names a colour in the palette and fits its line,
+	// and each colour covers the same number of cells in the rows and columns
+
+	pub fn validate (& self) -> Result <(), CluesError> {
+
+		let rows = (0 .. self.num_rows ()).map (
+			|index| (LineRef::Row (index), self.row (index), self.row_colours (index), self.num_cols ()),
+		);
+
+		let cols = (0 .. self.num_cols ()).map (
+			|index| (LineRef::Col (index), self.col (index), self.col_colours (index), self.num_rows ()),
+		);
+
+		for (line_ref, clues_line, colours, line_size) in rows.chain (cols) {
+
+			if clues_line.contains (& 0) {
+				return Err (CluesError::ZeroClue (line_ref));
+			}
+
+			if let Some (& colour) = colours.iter ().find (|& & colour| colour == 0 || colour > self.num_colours ()) {
+				return Err (CluesError::UnknownColour {
+					line_ref: line_ref,
+					colour: colour,
+					num_colours: self.num_colours (),
+				});
+			}
+
+			let min_size = clues_line_min_size (clues_line, colours);
+
+			if min_size > line_size as usize {
+				return Err (CluesError::LineTooShort {
+					line_ref: line_ref,
+					min_size: min_size,
+					line_size: line_size,
+				});
+			}
+
+		}
+
+		for colour in 1 ..= self.num_colours () {
+
+			let rows_sum = colour_sum (& self.rows, & self.row_colours, colour);
+			let cols_sum = colour_sum (& self.cols, & self.col_colours, colour);
+
+			if rows_sum != cols_sum {
+				return Err (CluesError::Unbalanced {
+					colour: self.palette.get (colour as usize - 1).map (
+						|colour| colour.name.clone (),
+					),
+					rows_sum: rows_sum,
+					cols_sum: cols_sum,
+				});
+			}
+
+		}
+
+		Ok (())
+
+	}
+
 	pub fn rows_sum (& self) -> usize {
 		self.rows.iter ().flatten ().map (|val| * val as usize).sum ()
 	}
@@ -136,8 +497,267 @@ impl Clues {
 	}
 
 	pub fn is_consistent (& self) -> bool {
+
+		// every colour must cover the same number of cells both ways
+
 		self.rows_sum () == self.cols_sum ()
+			&& (1 ..= self.num_colours ()).all (
+				|colour| colour_sum (& self.rows, & self.row_colours, colour)
+					== colour_sum (& self.cols, & self.col_colours, colour),
+			)
+
+	}
+
+}
+
+// the fewest cells the clues fit in, with gaps only between clues of the same
+// colour
+
+fn clues_line_min_size (clues_line: & CluesLine, colours: & [u8]) -> usize {
+
+	let gaps = colours.windows (2).filter (
+		|pair| pair [0] == pair [1],
+	).count ();
+
+	clues_line.iter ().map (|& clue| clue as usize).sum::<usize> () + gaps
+
+}
+
+// the first line with a different number of colours than clues, counting a
+// line missing from either side as having none
+
+fn find_colours_mismatch (
+	clues_lines: & [CluesLine],
+	colours_lines: & [Vec <u8>],
+) -> Option <usize> {
+
+	(0 .. clues_lines.len ().max (colours_lines.len ())).find (
+		|& index| clues_lines.get (index).map (Vec::len) != colours_lines.get (index).map (Vec::len),
+	)
+
+}
+
+fn colour_sum (
+	clues_lines: & [CluesLine],
+	colours_lines: & [Vec <u8>],
+	colour: u8,
+) -> usize {
+
+	clues_lines.iter ().flatten ().zip (colours_lines.iter ().flatten ()).filter (
+		|& (_, & clue_colour)| clue_colour == colour,
+	).map (
+		|(& clue, _)| clue as usize,
+	).sum ()
+
+}
+
+// a palette entry is a name and a hex colour, for example "r ff0000", and
+// errors are returned with the offset of the token at fault
+
+fn parse_colour (
+	tokens: & [(usize, & str)],
+) -> Result <Colour, (usize, CluesParseErrorKind)> {
+
+	let (name_column, name) = tokens [0];
+
+	if name.starts_with (|ch: char| ch.is_ascii_digit ()) {
+		return Err ((name_column, CluesParseErrorKind::BadColour (name.to_string ())));
 	}
 
+	let (hex_column, hex) = match tokens.get (1) {
+		Some (& val) => val,
+		None => return Err ((name_column, CluesParseErrorKind::BadColour (name.to_string ()))),
+	};
+
+	if hex.len () != 6 || ! hex.chars ().all (|ch| ch.is_ascii_hexdigit ()) {
+		return Err ((hex_column, CluesParseErrorKind::BadColour (hex.to_string ())));
+	}
+
+	if let Some (& (column, text)) = tokens.get (2) {
+		return Err ((column, CluesParseErrorKind::TrailingData (text.to_string ())));
+	}
+
+	let component = |index: usize| u8::from_str_radix (& hex [index .. index + 2], 16).unwrap ();
+
+	Ok (Colour {
+		name: name.to_string (),
+		red: component (0),
+		green: component (2),
+		blue: component (4),
+	})
+
 }
 
+// splits a line into whitespace separated tokens, each with its byte offset
+
+fn tokens (line: & str) -> Vec <(usize, & str)> {
+
+	let mut tokens = Vec::new ();
+	let mut start = None;
+
+	for (index, ch) in line.char_indices () {
+		match (ch.is_whitespace (), start) {
+			(true, Some (token_start)) => {
+				tokens.push ((token_start, & line [token_start .. index]));
+				start = None;
+			},
+			(false, None) => start = Some (index),
+			_ => (),
+		}
+	}
+
+	if let Some (token_start) = start {
+		tokens.push ((token_start, & line [token_start .. ]));
+	}
+
+	tokens
+
+}
+
+// the runs of solved cells of the same colour, ignoring any unsolved cells
+
+pub (super) fn line_runs (line: & Line) -> (CluesLine, Vec <u8>) {
+
+	let mut runs = Vec::new ();
+	let mut colours = Vec::new ();
+	let mut last = None;
+
+	for cell in line.iter () {
+
+		let colour = cell.colour_index ();
+
+		if let Some (colour) = colour {
+			if last == Some (colour) {
+				* runs.last_mut ().unwrap () += 1;
+			} else {
+				runs.push (1);
+				colours.push (colour);
+			}
+		}
+
+		last = colour;
+
+	}
+
+	(runs, colours)
+
+}
+
+#[ cfg (test) ]
+mod tests {
+
+	use super::*;
+	use crate::solver::*;
+
+	// the clues of each solved sample are the ones it was loaded from
+
+	#[ test ]
+	fn test_clues_from_grid () {
+
+		for name in [ "camel", "wikipedia", "colours" ] {
+
+			let grid_solver = solve_sample (name);
+			let clues = grid_solver.clues ();
+
+			let derived = Clues::from_grid_with_palette (
+				grid_solver.grid (),
+				clues.palette ().to_vec (),
+			).unwrap ();
+
+			assert_eq! (format! ("{:?}", derived), format! ("{:?}", clues), "{}", name);
+
+		}
+
+	}
+
+	// colour grids get a default palette
+
+	#[ test ]
+	fn test_clues_from_grid_colours () {
+
+		let mut grid = Grid::with_colours (1, 3, 2);
+		grid [(0, 0)] = Cell::colour (1);
+		grid [(0, 1)] = Cell::colour (2);
+		grid [(0, 2)] = Cell::EMPTY;
+
+		let clues = Clues::from_grid (& grid);
+
+		assert! (clues.is_coloured ());
+		assert_eq! (clues.num_colours (), 2);
+		assert_eq! (clues.row (0), & vec! [ 1, 1 ]);
+		assert_eq! (clues.row_colours (0), & [ 1, 2 ]);
+		assert! (clues.validate ().is_ok ());
+		assert! (clues.check (& grid).is_ok ());
+
+		// and solving them gives the grid back
+
+		let solutions: Vec <Grid> = find_solutions (& clues).collect ();
+		assert_eq! (solutions, vec! [ grid.clone () ]);
+
+		assert_eq! (
+			Clues::from_grid_with_palette (& grid, Vec::new ()).unwrap_err ().to_string (),
+			"grid uses colours missing from the palette",
+		);
+
+		assert_eq! (
+			Clues::from_grid_with_palette (& Grid::new (1, 1), Vec::new ()).unwrap_err ().to_string (),
+			"grid isn't solved",
+		);
+
+	}
+
+	fn load_error (source: & str) -> String {
+		Clues::load (& mut source.as_bytes ()).unwrap_err ().to_string ()
+	}
+
+	#[ test ]
+	fn test_clues_load_errors () {
+
+		assert_eq! (load_error ("rows\n1 x2\ncols\n1\n"), "line 2, column 3: bad number \"x2\"");
+		assert_eq! (load_error ("rows\n1 2x\ncols\n1\n"), "line 2, column 4: trailing data \"x\"");
+		assert_eq! (load_error ("rows\n1\nrows\n"), "line 3, column 1: unexpected rows section");
+		assert_eq! (load_error ("rows\n1\ncols\n1\ncols\n"), "line 5, column 1: unexpected cols section");
+		assert_eq! (load_error ("rows\n1\n\n  cols 2\n1\n"), "line 4, column 8: trailing data \"2\"");
+		assert_eq! (load_error ("1\nrows\n"), "line 1, column 1: missing rows section");
+		assert_eq! (load_error ("cols\n1\n"), "line 1, column 1: missing rows section");
+		assert_eq! (load_error ("rows\n1\n1\n"), "line 4, column 1: missing cols section");
+		assert_eq! (load_error (""), "line 1, column 1: missing rows section");
+
+		assert_eq! (
+			load_error ("colours\nr ff0000\nrows\n1r 1g\ncols\n1r\n"),
+			"line 4, column 5: unknown colour \"g\"",
+		);
+
+		assert_eq! (
+			load_error ("colours\nr ff00zz\n"),
+			"line 2, column 3: bad colour \"ff00zz\"",
+		);
+
+		assert_eq! (
+			load_error ("colours\nré ff0000\nrows\n1ré 1x\n"),
+			"line 4, column 6: unknown colour \"x\"",
+		);
+
+		assert_eq! (load_error ("\u{feff}rows 2\n"), "line 1, column 6: trailing data \"2\"");
+		assert! (Clues::load (& mut "\u{feff}rows\n1\ncols\n1\n".as_bytes ()).is_ok ());
+
+		assert_eq! (
+			Clues::load (& mut "rows\n1\n".as_bytes ()).unwrap_err ().kind (),
+			Some (& CluesParseErrorKind::MissingSection ("cols".to_string ())),
+		);
+
+	}
+
+	#[ test ]
+	fn test_clues_from_grid_empty () {
+
+		let grid = Grid::load (& mut "# #\n   \n".as_bytes ()).unwrap ();
+		let clues = Clues::from_grid (& grid);
+
+		assert_eq! (clues.rows ().cloned ().collect::<Vec <CluesLine>> (), vec! [ vec! [ 1, 1 ], vec! [ ] ]);
+		assert_eq! (clues.cols ().cloned ().collect::<Vec <CluesLine>> (), vec! [ vec! [ 1 ], vec! [ ], vec! [ 1 ] ]);
+		assert! (! clues.is_coloured ());
+
+	}
+
+}
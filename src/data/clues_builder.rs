@@ -0,0 +1,206 @@
+use crate::data::*;
+
+// builds clues a line at a time, checking them with Clues::validate at the end
+
+#[ derive (Clone, Debug, Default) ]
+pub struct CluesBuilder {
+	rows: Vec <CluesLine>,
+	cols: Vec <CluesLine>,
+	row_colours: Vec <Vec <u8>>,
+	col_colours: Vec <Vec <u8>>,
+	palette: Vec <Colour>,
+}
+
+impl CluesBuilder {
+
+	pub fn new () -> CluesBuilder {
+		Default::default ()
+	}
+
+	pub fn colour (mut self, colour: Colour) -> CluesBuilder {
+		self.palette.push (colour);
+		self
+	}
+
+	pub fn row (self, clues_line: CluesLine) -> CluesBuilder {
+		let colours = vec! [1; clues_line.len ()];
+		self.coloured_row (clues_line, colours)
+	}
+
+	pub fn col (self, clues_line: CluesLine) -> CluesBuilder {
+		let colours = vec! [1; clues_line.len ()];
+		self.coloured_col (clues_line, colours)
+	}
+
+	pub fn coloured_row (mut self, clues_line: CluesLine, colours: Vec <u8>) -> CluesBuilder {
+		self.rows.push (clues_line);
+		self.row_colours.push (colours);
+		self
+	}
+
+	pub fn coloured_col (mut self, clues_line: CluesLine, colours: Vec <u8>) -> CluesBuilder {
+		self.cols.push (clues_line);
+		self.col_colours.push (colours);
+		self
+	}
+
+	pub fn build (self) -> Result <Clues, CluesError> {
+
+		let clues = Clues::with_colours (
+			self.rows,
+			self.cols,
+			self.row_colours,
+			self.col_colours,
+			self.palette,
+		) ?;
+
+		clues.validate () ?;
+
+		Ok (clues)
+
+	}
+
+}
+
+#[ cfg (test) ]
+mod tests {
+
+	use super::*;
+	use crate::solver::LineRef;
+
+	fn build_error (builder: CluesBuilder) -> String {
+		builder.build ().unwrap_err ().to_string ()
+	}
+
+	#[ test ]
+	fn test_clues_builder () {
+
+		let clues = Clues::builder ()
+			.row (vec! [ 1, 1 ])
+			.row (vec! [ ])
+			.col (vec! [ 1 ])
+			.col (vec! [ ])
+			.col (vec! [ 1 ])
+			.build ()
+			.unwrap ();
+
+		assert_eq! ((clues.num_rows (), clues.num_cols ()), (2, 3));
+		assert_eq! (clues.row (0), & vec! [ 1, 1 ]);
+
+		assert_eq! (
+			build_error (Clues::builder ().row (vec! [ 1, 0 ]).col (vec! [ 1 ]).col (vec! [ ])),
+			"row 0 has a zero clue",
+		);
+
+		assert_eq! (
+			build_error (Clues::builder ().row (vec! [ 1, 1 ]).col (vec! [ 1 ]).col (vec! [ 1 ])),
+			"row 0 needs 3 cells but only has 2",
+		);
+
+		assert_eq! (
+			build_error (Clues::builder ().row (vec! [ 1 ]).col (vec! [ 1 ]).col (vec! [ 1 ])),
+			"clues cover 1 cells in the rows but 2 in the cols",
+		);
+
+		assert_eq! (
+			build_error (Clues::builder ().coloured_row (vec! [ 1 ], vec! [ ]).col (vec! [ 1 ])),
+			"row 0 needs exactly one colour for each clue",
+		);
+
+	}
+
+	#[ test ]
+	fn test_clues_builder_colours () {
+
+		let red = Colour { name: "r".to_string (), red: 255, green: 0, blue: 0 };
+		let blue = Colour { name: "b".to_string (), red: 0, green: 0, blue: 255 };
+
+		// different colours don't need a gap, so both clues fit in two cells
+
+		let clues = Clues::builder ()
+			.colour (red.clone ())
+			.colour (blue.clone ())
+			.coloured_row (vec! [ 1, 1 ], vec! [ 1, 2 ])
+			.coloured_col (vec! [ 1 ], vec! [ 1 ])
+			.coloured_col (vec! [ 1 ], vec! [ 2 ])
+			.build ()
+			.unwrap ();
+
+		assert! (clues.is_coloured ());
+		assert_eq! (clues.row_colours (0), & [ 1, 2 ]);
+
+		assert_eq! (
+			build_error (
+				Clues::builder ()
+					.colour (red.clone ())
+					.colour (blue.clone ())
+					.coloured_row (vec! [ 1, 1 ], vec! [ 1, 2 ])
+					.coloured_col (vec! [ 1 ], vec! [ 1 ])
+					.coloured_col (vec! [ 1 ], vec! [ 1 ]),
+			),
+			"clues of colour r cover 1 cells in the rows but 2 in the cols",
+		);
+
+		assert_eq! (
+			build_error (
+				Clues::builder ()
+					.colour (red)
+					.colour (blue)
+					.coloured_row (vec! [ 1 ], vec! [ 3 ])
+					.coloured_col (vec! [ 1 ], vec! [ 3 ]),
+			),
+			"row 0 uses colour 3 but there are only 2 colours",
+		);
+
+		let too_many = (0 ..= MAX_COLOURS).fold (
+			Clues::builder (),
+			|builder, index| builder.colour (Colour {
+				name: index.to_string (),
+				red: 0,
+				green: 0,
+				blue: 0,
+			}),
+		);
+
+		assert_eq! (too_many.build ().unwrap_err (), CluesError::TooManyColours);
+
+	}
+
+	#[ test ]
+	fn test_clues_set_lines () {
+
+		let mut clues = Clues::builder ()
+			.row (vec! [ 2 ])
+			.row (vec! [ ])
+			.col (vec! [ 1 ])
+			.col (vec! [ 1 ])
+			.build ()
+			.unwrap ();
+
+		clues.set_row (0, vec! [ 1 ]).unwrap ();
+		clues.set_row (1, vec! [ 1 ]).unwrap ();
+		assert! (clues.validate ().is_ok ());
+
+		clues.set_col (1, vec! [ 2 ]).unwrap ();
+		assert_eq! (
+			clues.validate (),
+			Err (CluesError::Unbalanced { colour: None, rows_sum: 2, cols_sum: 3 }),
+		);
+
+		clues.set_col (0, vec! [ ]).unwrap ();
+		assert! (clues.validate ().is_ok ());
+		assert_eq! (clues.col (1), & vec! [ 2 ]);
+
+		// lines outside the puzzle, or with the wrong number of colours, are
+		// rejected without changing anything
+
+		assert_eq! (clues.set_row (2, vec! [ 1 ]), Err (CluesError::LineOutOfRange (LineRef::Row (2))));
+		assert_eq! (
+			clues.set_coloured_col (0, vec! [ 1 ], vec! [ ]),
+			Err (CluesError::ColoursMismatch (LineRef::Col (0))),
+		);
+		assert_eq! (clues.col (0), & vec! [ ]);
+
+	}
+
+}
@@ -0,0 +1,191 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use crate::data::*;
+use crate::solver::LineRef;
+
+#[ derive (Debug) ]
+pub enum CluesParseError {
+	Io (io::Error),
+	Syntax {
+		line: usize,
+		column: usize,
+		kind: CluesParseErrorKind,
+	},
+}
+
+#[ derive (Clone, Debug, Eq, PartialEq) ]
+pub enum CluesParseErrorKind {
+
+	// a section header in the wrong place, or a repeated one
+
+	UnexpectedSection (String),
+
+	// a clue which isn't a number, or is too big
+
+	BadNumber (String),
+
+	// a section which must come before this point, or at all
+
+	MissingSection (String),
+
+	// anything left over after a section header, clue or colour
+
+	TrailingData (String),
+
+	UnknownColour (String),
+	BadColour (String),
+	TooManyColours,
+
+}
+
+// clues which can't describe a puzzle, from building, changing or checking them
+
+#[ derive (Clone, Debug, Eq, PartialEq) ]
+pub enum CluesError {
+
+	TooManyColours,
+
+	// a line with a different number of colours than clues
+
+	ColoursMismatch (LineRef),
+
+	// a line beyond the edge of the puzzle
+
+	LineOutOfRange (LineRef),
+
+	ZeroClue (LineRef),
+
+	UnknownColour {
+		line_ref: LineRef,
+		colour: u8,
+		num_colours: u8,
+	},
+
+	LineTooShort {
+		line_ref: LineRef,
+		min_size: usize,
+		line_size: LineSize,
+	},
+
+	// the rows and cols cover a different number of cells of one colour, named
+	// if the clues have a palette
+
+	Unbalanced {
+		colour: Option <String>,
+		rows_sum: usize,
+		cols_sum: usize,
+	},
+
+	GridNotSolved,
+	GridColoursMissing,
+
+}
+
+impl CluesParseError {
+
+	pub fn kind (& self) -> Option <& CluesParseErrorKind> {
+		match * self {
+			CluesParseError::Io (_) => None,
+			CluesParseError::Syntax { ref kind, .. } => Some (kind),
+		}
+	}
+
+}
+
+impl fmt::Display for CluesParseError {
+
+	fn fmt (& self, formatter: & mut fmt::Formatter <'_>) -> fmt::Result {
+
+		match * self {
+			CluesParseError::Io (ref error) =>
+				write! (formatter, "{}", error),
+			CluesParseError::Syntax { line, column, ref kind } =>
+				write! (formatter, "line {}, column {}: {}", line, column, kind),
+		}
+
+	}
+
+}
+
+impl fmt::Display for CluesParseErrorKind {
+
+	fn fmt (& self, formatter: & mut fmt::Formatter <'_>) -> fmt::Result {
+
+		match * self {
+			CluesParseErrorKind::UnexpectedSection (ref name) =>
+				write! (formatter, "unexpected {} section", name),
+			CluesParseErrorKind::BadNumber (ref text) =>
+				write! (formatter, "bad number \"{}\"", text),
+			CluesParseErrorKind::MissingSection (ref name) =>
+				write! (formatter, "missing {} section", name),
+			CluesParseErrorKind::TrailingData (ref text) =>
+				write! (formatter, "trailing data \"{}\"", text),
+			CluesParseErrorKind::UnknownColour (ref name) =>
+				write! (formatter, "unknown colour \"{}\"", name),
+			CluesParseErrorKind::BadColour (ref text) =>
+				write! (formatter, "bad colour \"{}\"", text),
+			CluesParseErrorKind::TooManyColours =>
+				write! (formatter, "too many colours, maximum is {}", MAX_COLOURS),
+		}
+
+	}
+
+}
+
+impl fmt::Display for CluesError {
+
+	fn fmt (& self, formatter: & mut fmt::Formatter <'_>) -> fmt::Result {
+
+		match * self {
+			CluesError::TooManyColours =>
+				write! (formatter, "too many colours, maximum is {}", MAX_COLOURS),
+			CluesError::ColoursMismatch (line_ref) =>
+				write! (formatter, "{} needs exactly one colour for each clue", line_ref),
+			CluesError::LineOutOfRange (line_ref) =>
+				write! (formatter, "{} is outside the puzzle", line_ref),
+			CluesError::ZeroClue (line_ref) =>
+				write! (formatter, "{} has a zero clue", line_ref),
+			CluesError::UnknownColour { line_ref, colour, num_colours } =>
+				write! (formatter, "{} uses colour {} but there are only {} colours", line_ref, colour, num_colours),
+			CluesError::LineTooShort { line_ref, min_size, line_size } =>
+				write! (formatter, "{} needs {} cells but only has {}", line_ref, min_size, line_size),
+			CluesError::Unbalanced { ref colour, rows_sum, cols_sum } => {
+				match * colour {
+					Some (ref name) => write! (formatter, "clues of colour {}", name) ?,
+					None => write! (formatter, "clues") ?,
+				}
+				write! (formatter, " cover {} cells in the rows but {} in the cols", rows_sum, cols_sum)
+			},
+			CluesError::GridNotSolved =>
+				write! (formatter, "grid isn't solved"),
+			CluesError::GridColoursMissing =>
+				write! (formatter, "grid uses colours missing from the palette"),
+		}
+
+	}
+
+}
+
+impl Error for CluesError {
+}
+
+impl Error for CluesParseError {
+
+	fn source (& self) -> Option <& (dyn Error + 'static)> {
+		match * self {
+			CluesParseError::Io (ref error) => Some (error),
+			CluesParseError::Syntax { .. } => None,
+		}
+	}
+
+}
+
+impl From <io::Error> for CluesParseError {
+
+	fn from (error: io::Error) -> CluesParseError {
+		CluesParseError::Io (error)
+	}
+
+}
@@ -1,12 +1,20 @@
+use std::fs::File;
+use std::io;
+use std::io::BufRead;
+use std::io::BufReader;
 use std::iter;
 use std::ops;
+use std::path::Path;
+use std::str::FromStr;
 
 use crate::data::*;
 
+#[ derive (Clone, Debug, Eq, PartialEq) ]
 pub struct Grid {
 	data: Vec <Cell>,
 	num_rows: LineSize,
 	num_cols: LineSize,
+	num_colours: u8,
 }
 
 impl Grid {
@@ -18,14 +26,94 @@ impl Grid {
 		num_cols: LineSize,
 	) -> Grid {
 
+		Grid::with_colours (num_rows, num_cols, 1)
+
+	}
+
+	pub fn with_colours (
+		num_rows: LineSize,
+		num_cols: LineSize,
+		num_colours: u8,
+	) -> Grid {
+
 		Grid {
-			data: iter::repeat (Cell::UNKNOWN).take (
+			data: iter::repeat_n (
+				Cell::unknown (num_colours),
 				num_rows as usize * num_cols as usize,
 			).collect (),
 			num_rows: num_rows,
 			num_cols: num_cols,
+			num_colours: num_colours,
+		}
+
+	}
+
+	pub fn load_file (
+		filename: & Path,
+	) -> Result <Grid, GridLoadError> {
+
+		let mut file = File::open (
+			filename,
+		) ?;
+
+		Grid::load (& mut file)
+
+	}
+
+	// one line per row, using the same characters as lines, so '-' for unknown,
+	// ' ' for empty and '#' for filled, and blank lines are ignored; there are
+	// no characters for colours, so only monochrome grids can be loaded
+
+	pub fn load (
+		reader: & mut dyn io::Read,
+	) -> Result <Grid, GridLoadError> {
+
+		let reader = BufReader::new (
+			reader,
+		);
+
+		let mut data = Vec::new ();
+		let mut num_rows: LineSize = 0;
+		let mut num_cols: Option <LineSize> = None;
+
+		for result in reader.split (b'\n') {
+
+			let line = result ?;
+			let line = String::from_utf8_lossy (& line);
+			let line = line.strip_suffix ('\r').unwrap_or (& line);
+
+			if line.is_empty () {
+				continue;
+			}
+
+			let row = LineBuf::from_str (line).map_err (
+				|error| GridLoadError::BadCell {
+					row: num_rows,
+					ch: error.ch,
+				},
+			) ?;
+
+			match num_cols {
+				Some (num_cols) if num_cols != row.len () => return Err (GridLoadError::RowLength {
+					row: num_rows,
+					len: row.len (),
+					expected: num_cols,
+				}),
+				_ => num_cols = Some (row.len ()),
+			}
+
+			data.extend (row.iter ());
+			num_rows += 1;
+
 		}
 
+		Ok (Grid {
+			data: data,
+			num_rows: num_rows,
+			num_cols: num_cols.unwrap_or (0),
+			num_colours: 1,
+		})
+
 	}
 
 	// getters
@@ -38,12 +126,33 @@ impl Grid {
 		self.num_cols
 	}
 
+	pub fn num_colours (& self) -> u8 {
+		self.num_colours
+	}
+
 	pub fn is_solved (& self) -> bool {
 		self.data.iter ().all (
 			|cell| cell.is_solved (),
 		)
 	}
 
+	pub fn unknown_cells <'a> (
+		& 'a self,
+	) -> impl Iterator <Item = (LineSize, LineSize)> + 'a {
+
+		let num_cols = self.num_cols as usize;
+
+		self.data.iter ().enumerate ().filter (
+			|(_, cell)| cell.is_unknown (),
+		).map (
+			move |(index, _)| (
+				(index / num_cols) as LineSize,
+				(index % num_cols) as LineSize,
+			),
+		)
+
+	}
+
 	pub fn row <'a> (
 		& 'a self,
 		row_index: LineSize,
@@ -142,7 +251,7 @@ impl <'a> Iterator for GridIter <'a> {
 
 	fn next (& mut self) -> Option <Cell> {
 
-		let result = self.data.get (0);
+		let result = self.data.first ();
 
 		let step = usize::min (self.step, self.data.len ());
 		self.data = & self.data [step .. ];
@@ -153,3 +262,42 @@ impl <'a> Iterator for GridIter <'a> {
 
 }
 
+#[ cfg (test) ]
+mod tests {
+
+	use super::*;
+
+	#[ test ]
+	fn test_grid_load () {
+
+		let grid = Grid::load (& mut "-# \r\n\n# -\n".as_bytes ()).unwrap ();
+
+		assert_eq! ((grid.num_rows (), grid.num_cols ()), (2, 3));
+		assert_eq! (grid.row (0).collect::<LineBuf> (), LineBuf::from_str ("-# ").unwrap ());
+		assert_eq! (grid.row (1).collect::<LineBuf> (), LineBuf::from_str ("# -").unwrap ());
+
+		assert_eq! (
+			Grid::load (& mut "--\n---\n".as_bytes ()).unwrap_err ().to_string (),
+			"grid row 1 has 3 cells, expected 2",
+		);
+
+		assert_eq! (
+			Grid::load (& mut "-x\n".as_bytes ()).unwrap_err ().to_string (),
+			"bad cell \"x\" in grid row 0",
+		);
+
+	}
+
+	#[ test ]
+	fn test_grid_unknown_cells_colours () {
+
+		let mut grid = Grid::with_colours (1, 3, 2);
+		grid [(0, 0)] = Cell::colour (2);
+		grid [(0, 1)] = Cell::colour (1).union (Cell::EMPTY);
+
+		assert_eq! (grid.unknown_cells ().collect::<Vec <_>> (), vec! [ (0, 1), (0, 2) ]);
+		assert! (! grid.is_solved ());
+
+	}
+
+}
@@ -0,0 +1,62 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use crate::data::*;
+
+#[ derive (Debug) ]
+pub enum GridLoadError {
+
+	Io (io::Error),
+
+	// a character which isn't a monochrome cell, which includes any attempt at
+	// a colour grid
+
+	BadCell {
+		row: LineSize,
+		ch: char,
+	},
+
+	RowLength {
+		row: LineSize,
+		len: LineSize,
+		expected: LineSize,
+	},
+
+}
+
+impl fmt::Display for GridLoadError {
+
+	fn fmt (& self, formatter: & mut fmt::Formatter <'_>) -> fmt::Result {
+
+		match * self {
+			GridLoadError::Io (ref error) =>
+				write! (formatter, "{}", error),
+			GridLoadError::BadCell { row, ch } =>
+				write! (formatter, "bad cell \"{}\" in grid row {}", ch, row),
+			GridLoadError::RowLength { row, len, expected } =>
+				write! (formatter, "grid row {} has {} cells, expected {}", row, len, expected),
+		}
+
+	}
+
+}
+
+impl Error for GridLoadError {
+
+	fn source (& self) -> Option <& (dyn Error + 'static)> {
+		match * self {
+			GridLoadError::Io (ref error) => Some (error),
+			_ => None,
+		}
+	}
+
+}
+
+impl From <io::Error> for GridLoadError {
+
+	fn from (error: io::Error) -> GridLoadError {
+		GridLoadError::Io (error)
+	}
+
+}
@@ -36,6 +36,10 @@ impl Line {
 		self.cells.len () as LineSize
 	}
 
+	pub fn is_empty (& self) -> bool {
+		self.cells.is_empty ()
+	}
+
 	pub fn is_solved (& self) -> bool {
 		! self.iter ().any (Cell::is_unknown)
 	}
@@ -83,7 +87,7 @@ impl Index <Range <LineSize>> for Line {
 	type Output = Line;
 
 	fn index (& self, range: Range <LineSize>) -> & Line {
-		& Line::new (& self.cells [
+		Line::new (& self.cells [
 			Range {
 				start: range.start as usize,
 				end: range.end as usize,
@@ -98,7 +102,7 @@ impl Index <RangeFrom <LineSize>> for Line {
 	type Output = Line;
 
 	fn index (& self, range: RangeFrom <LineSize>) -> & Line {
-		& Line::new (& self.cells [
+		Line::new (& self.cells [
 			RangeFrom {
 				start: range.start as usize,
 			}
@@ -112,7 +116,7 @@ impl Index <RangeTo <LineSize>> for Line {
 	type Output = Line;
 
 	fn index (& self, range: RangeTo <LineSize>) -> & Line {
-		& Line::new (& self.cells [
+		Line::new (& self.cells [
 			RangeTo {
 				end: range.end as usize,
 			}
@@ -123,7 +127,7 @@ impl Index <RangeTo <LineSize>> for Line {
 
 impl IndexMut <LineSize> for Line {
 
-	fn index_mut <'a> (& 'a mut self, index: LineSize) -> & 'a mut Cell {
+	fn index_mut (& mut self, index: LineSize) -> & mut Cell {
 		& mut self.cells [index as usize]
 	}
 
@@ -131,7 +135,7 @@ impl IndexMut <LineSize> for Line {
 
 impl IndexMut <Range <LineSize>> for Line {
 
-	fn index_mut <'a> (& 'a mut self, range: Range <LineSize>) -> & 'a mut Line {
+	fn index_mut (& mut self, range: Range <LineSize>) -> & mut Line {
 		Line::new_mut (& mut self.cells [
 			Range {
 				start: range.start as usize,
@@ -142,6 +146,17 @@ impl IndexMut <Range <LineSize>> for Line {
 
 }
 
+impl <'a> IntoIterator for & 'a Line {
+
+	type Item = Cell;
+	type IntoIter = Cloned <slice::Iter <'a, Cell>>;
+
+	fn into_iter (self) -> Cloned <slice::Iter <'a, Cell>> {
+		self.cells.iter ().cloned ()
+	}
+
+}
+
 impl fmt::Debug for Line {
 
 	fn fmt (& self, formatter: & mut fmt::Formatter <'_>) -> fmt::Result {
@@ -153,11 +168,14 @@ impl fmt::Debug for Line {
 				|& cell|
 
 				match cell {
-					Cell::UNKNOWN => "-",
-					Cell::EMPTY => " ",
-					Cell::FILLED => "#",
-					Cell::ERROR => "!",
-					_ => "?",
+					Cell::UNKNOWN => '-',
+					Cell::EMPTY => ' ',
+					Cell::FILLED => '#',
+					Cell::ERROR => '!',
+					_ => match cell.colour_index () {
+						Some (index) => char::from (b'0' + index),
+						None => '?',
+					},
 				}
 
 			).collect::<String> (),
@@ -170,6 +188,7 @@ impl fmt::Debug for Line {
 }
 
 #[ cfg (test) ]
+#[ allow (clippy::useless_vec) ]
 mod tests {
 
 	use super::*;
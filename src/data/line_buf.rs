@@ -1,14 +1,16 @@
 use std::borrow::Borrow;
+use std::error::Error;
 use std::fmt;
 use std::iter;
 use std::iter::FromIterator;
 use std::ops::Deref;
 use std::ops::DerefMut;
 use std::slice;
+use std::str::FromStr;
 
 use crate::*;
 
-#[ derive (Default, PartialEq) ]
+#[ derive (Clone, Default, Eq, Hash, PartialEq) ]
 pub struct LineBuf {
 	cells: Vec <Cell>,
 }
@@ -31,34 +33,11 @@ impl LineBuf {
 	pub fn with_size (size: LineSize) -> LineBuf {
 
 		LineBuf {
-			cells: iter::repeat (Cell::UNKNOWN).take (size as usize).collect (),
+			cells: iter::repeat_n (Cell::UNKNOWN, size as usize).collect (),
 		}
 
 	}
 
-	pub fn from_str (
-		source: & str,
-	) -> Option <LineBuf> {
-
-		Some (LineBuf {
-
-			cells: match source.bytes ().map (
-				|ch| match ch {
-					b'-' => Some (Cell::UNKNOWN),
-					b' ' => Some (Cell::EMPTY),
-					b'#' => Some (Cell::FILLED),
-					b'!' => Some (Cell::EMPTY),
-					_ => None,
-				}
-			).collect () {
-				Some (val) => val,
-				None => return None,
-			},
-
-		})
-
-	}
-
 	pub fn capacity (& self) -> LineSize {
 		self.cells.capacity () as LineSize
 	}
@@ -126,6 +105,32 @@ impl FromIterator <Cell> for LineBuf {
 
 }
 
+impl FromStr for LineBuf {
+
+	type Err = LineBufParseError;
+
+	fn from_str (
+		source: & str,
+	) -> Result <LineBuf, LineBufParseError> {
+
+		Ok (LineBuf {
+
+			cells: source.chars ().map (
+				|ch| match ch {
+					'-' => Ok (Cell::UNKNOWN),
+					' ' => Ok (Cell::EMPTY),
+					'#' => Ok (Cell::FILLED),
+					'!' => Ok (Cell::EMPTY),
+					_ => Err (LineBufParseError { ch: ch }),
+				}
+			).collect::<Result <_, _>> () ?,
+
+		})
+
+	}
+
+}
+
 impl IntoDefault for LineBuf {
 
 	fn into_default (self) -> LineBuf {
@@ -147,6 +152,22 @@ impl <'a> IntoIterator for & 'a LineBuf {
 
 }
 
+#[ derive (Clone, Copy, Debug, Eq, PartialEq) ]
+pub struct LineBufParseError {
+	pub ch: char,
+}
+
+impl fmt::Display for LineBufParseError {
+
+	fn fmt (& self, formatter: & mut fmt::Formatter <'_>) -> fmt::Result {
+		write! (formatter, "bad cell \"{}\"", self.ch)
+	}
+
+}
+
+impl Error for LineBufParseError {
+}
+
 #[ cfg (test) ]
 mod tests {
 
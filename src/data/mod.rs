@@ -1,12 +1,24 @@
 mod cell;
+mod check;
 mod clues;
+mod clues_builder;
+mod clues_error;
 mod grid;
+mod grid_error;
 mod line;
 mod line_buf;
+mod packed_grid;
+mod packed_line;
 
 pub use cell::*;
+pub use check::*;
 pub use clues::*;
+pub use clues_builder::*;
+pub use clues_error::*;
 pub use grid::*;
+pub use grid_error::*;
 pub use line::*;
 pub use line_buf::*;
+pub use packed_grid::*;
+pub use packed_line::*;
 
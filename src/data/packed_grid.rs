@@ -0,0 +1,163 @@
+use std::fmt;
+
+use crate::data::*;
+
+// grid stored as a packed line for every row and every column, kept in step
+// with each other, so that any line can be handed to the packed clue placement
+// without packing it first. packed cells only say whether they can be filled
+// or empty, so colour grids can't be packed.
+
+#[ derive (Clone, Default, Eq, PartialEq) ]
+pub struct PackedGrid {
+	rows: Vec <PackedLine>,
+	cols: Vec <PackedLine>,
+}
+
+impl PackedGrid {
+
+	pub fn new (
+		num_rows: LineSize,
+		num_cols: LineSize,
+	) -> PackedGrid {
+
+		PackedGrid {
+			rows: (0 .. num_rows).map (|_| PackedLine::with_size (num_cols)).collect (),
+			cols: (0 .. num_cols).map (|_| PackedLine::with_size (num_rows)).collect (),
+		}
+
+	}
+
+	// returns none for colour grids
+
+	pub fn from_grid (grid: & Grid) -> Option <PackedGrid> {
+
+		if grid.num_colours () != 1 {
+			return None;
+		}
+
+		Some (PackedGrid {
+			rows: (0 .. grid.num_rows ()).map (|index| grid.row (index).collect ()).collect (),
+			cols: (0 .. grid.num_cols ()).map (|index| grid.col (index).collect ()).collect (),
+		})
+
+	}
+
+	pub fn to_grid (& self) -> Grid {
+
+		let mut grid = Grid::new (self.num_rows (), self.num_cols ());
+
+		for (row_index, row) in self.rows.iter ().enumerate () {
+			for (col_index, cell) in row.iter ().enumerate () {
+				grid [(row_index as LineSize, col_index as LineSize)] = cell;
+			}
+		}
+
+		grid
+
+	}
+
+	pub fn num_rows (& self) -> LineSize {
+		self.rows.len () as LineSize
+	}
+
+	pub fn num_cols (& self) -> LineSize {
+		self.cols.len () as LineSize
+	}
+
+	pub fn row (& self, index: LineSize) -> & PackedLine {
+		& self.rows [index as usize]
+	}
+
+	pub fn col (& self, index: LineSize) -> & PackedLine {
+		& self.cols [index as usize]
+	}
+
+	pub fn get (& self, row_index: LineSize, col_index: LineSize) -> Cell {
+		self.rows [row_index as usize].get (col_index)
+	}
+
+	pub fn set (& mut self, row_index: LineSize, col_index: LineSize, cell: Cell) {
+		self.rows [row_index as usize].set (col_index, cell);
+		self.cols [col_index as usize].set (row_index, cell);
+	}
+
+	pub fn is_solved (& self) -> bool {
+		self.rows.iter ().all (
+			|row| row.iter ().all (Cell::is_solved),
+		)
+	}
+
+}
+
+impl fmt::Debug for PackedGrid {
+
+	fn fmt (& self, formatter: & mut fmt::Formatter <'_>) -> fmt::Result {
+		formatter.debug_list ().entries (self.rows.iter ()).finish ()
+	}
+
+}
+
+impl From <& PackedGrid> for Grid {
+
+	fn from (packed_grid: & PackedGrid) -> Grid {
+		packed_grid.to_grid ()
+	}
+
+}
+
+#[ cfg (test) ]
+mod tests {
+
+	use super::*;
+
+	#[ test ]
+	fn test_packed_grid_round_trip () {
+
+		let source = format! (
+			"{}\n{}\n{}\n",
+			"-#-".repeat (30),
+			"# -".repeat (30),
+			" --".repeat (30),
+		);
+
+		let grid = Grid::load (& mut source.as_bytes ()).unwrap ();
+
+		let packed_grid = PackedGrid::from_grid (& grid).unwrap ();
+
+		assert_eq! ((packed_grid.num_rows (), packed_grid.num_cols ()), (3, 90));
+		assert_eq! (Grid::from (& packed_grid), grid);
+
+		for col_index in 0 .. grid.num_cols () {
+			assert_eq! (
+				packed_grid.col (col_index).to_line_buf (),
+				grid.col (col_index).collect::<LineBuf> (),
+			);
+		}
+
+		assert! (PackedGrid::from_grid (& Grid::with_colours (1, 1, 2)).is_none ());
+
+	}
+
+	#[ test ]
+	fn test_packed_grid_set () {
+
+		let mut packed_grid = PackedGrid::new (2, 70);
+
+		packed_grid.set (1, 65, Cell::FILLED);
+		packed_grid.set (0, 65, Cell::EMPTY);
+
+		assert_eq! (packed_grid.get (1, 65), Cell::FILLED);
+		assert_eq! (packed_grid.row (1).get (65), Cell::FILLED);
+		assert_eq! (packed_grid.col (65).to_line_buf (), [ Cell::EMPTY, Cell::FILLED ].into_iter ().collect ());
+		assert! (packed_grid.col (65).all_can_fill (1, 2));
+		assert! (! packed_grid.is_solved ());
+
+		let mut grid = Grid::new (2, 70);
+		grid [(1, 65)] = Cell::FILLED;
+		grid [(0, 65)] = Cell::EMPTY;
+
+		assert_eq! (packed_grid.to_grid (), grid);
+
+	}
+
+}
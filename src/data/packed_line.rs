@@ -0,0 +1,280 @@
+use std::fmt;
+use std::iter::FromIterator;
+
+use crate::data::*;
+use crate::misc::*;
+
+const WORD_BITS: LineSize = 64;
+
+// line stored as two bitmasks, one bit per cell for "can fill" and "can empty",
+// so that runs of cells can be checked a word at a time
+
+#[ derive (Clone, Default, Eq, Hash, PartialEq) ]
+pub struct PackedLine {
+	len: LineSize,
+	can_fill: Vec <u64>,
+	can_empty: Vec <u64>,
+}
+
+impl PackedLine {
+
+	pub fn with_size (size: LineSize) -> PackedLine {
+
+		PackedLine::default ().into_copy_of (
+			(0 .. size).map (|_| Cell::UNKNOWN),
+		)
+
+	}
+
+	pub fn into_copy_of <
+		LineIter: IntoIterator <Item = Cell>,
+	> (
+		self,
+		line_iter: LineIter,
+	) -> PackedLine {
+
+		let mut packed_line = PackedLine {
+			len: 0,
+			can_fill: self.can_fill.into_default (),
+			can_empty: self.can_empty.into_default (),
+		};
+
+		for cell in line_iter {
+
+			if packed_line.len.is_multiple_of (WORD_BITS) {
+				packed_line.can_fill.push (0);
+				packed_line.can_empty.push (0);
+			}
+
+			packed_line.len += 1;
+			packed_line.set (packed_line.len - 1, cell);
+
+		}
+
+		packed_line
+
+	}
+
+	pub fn len (& self) -> LineSize {
+		self.len
+	}
+
+	pub fn is_empty (& self) -> bool {
+		self.len == 0
+	}
+
+	pub fn get (& self, index: LineSize) -> Cell {
+
+		assert! (index < self.len);
+
+		let (word, bit) = word_and_bit (index);
+
+		match (
+			self.can_fill [word] & bit != 0,
+			self.can_empty [word] & bit != 0,
+		) {
+			(true, true) => Cell::UNKNOWN,
+			(true, false) => Cell::FILLED,
+			(false, true) => Cell::EMPTY,
+			(false, false) => Cell::ERROR,
+		}
+
+	}
+
+	pub fn set (& mut self, index: LineSize, cell: Cell) {
+
+		assert! (index < self.len);
+
+		let (word, bit) = word_and_bit (index);
+
+		if cell.can_fill () {
+			self.can_fill [word] |= bit;
+		} else {
+			self.can_fill [word] &= ! bit;
+		}
+
+		if cell.can_empty () {
+			self.can_empty [word] |= bit;
+		} else {
+			self.can_empty [word] &= ! bit;
+		}
+
+	}
+
+	pub fn all_can_fill (& self, start: LineSize, end: LineSize) -> bool {
+		all_set (& self.can_fill, start, end)
+	}
+
+	pub fn all_can_empty (& self, start: LineSize, end: LineSize) -> bool {
+		all_set (& self.can_empty, start, end)
+	}
+
+	pub fn iter (& self) -> impl Iterator <Item = Cell> + '_ {
+		(0 .. self.len).map (move |index| self.get (index))
+	}
+
+	pub fn to_line_buf (& self) -> LineBuf {
+		self.iter ().collect ()
+	}
+
+}
+
+fn word_and_bit (index: LineSize) -> (usize, u64) {
+	(
+		(index / WORD_BITS) as usize,
+		1 << (index % WORD_BITS),
+	)
+}
+
+// true if every bit in [start, end) is set, checking whole words at once
+
+fn all_set (words: & [u64], start: LineSize, end: LineSize) -> bool {
+
+	if start >= end {
+		return true;
+	}
+
+	let first_word = (start / WORD_BITS) as usize;
+	let last_word = ((end - 1) / WORD_BITS) as usize;
+
+	for (word_index, & word) in words.iter ().enumerate ().take (last_word + 1).skip (first_word) {
+
+		let low = if word_index == first_word { start % WORD_BITS } else { 0 };
+		let high = if word_index == last_word { (end - 1) % WORD_BITS + 1 } else { WORD_BITS };
+
+		let mask = if high - low == WORD_BITS {
+			! 0
+		} else {
+			((1 << (high - low)) - 1) << low
+		};
+
+		if word & mask != mask {
+			return false;
+		}
+
+	}
+
+	true
+
+}
+
+impl fmt::Debug for PackedLine {
+
+	fn fmt (& self, formatter: & mut fmt::Formatter <'_>) -> fmt::Result {
+		fmt::Debug::fmt (& self.to_line_buf (), formatter)
+	}
+
+}
+
+impl From <& Line> for PackedLine {
+
+	fn from (line: & Line) -> PackedLine {
+		line.iter ().collect ()
+	}
+
+}
+
+impl From <& PackedLine> for LineBuf {
+
+	fn from (packed_line: & PackedLine) -> LineBuf {
+		packed_line.to_line_buf ()
+	}
+
+}
+
+impl FromIterator <Cell> for PackedLine {
+
+	fn from_iter <Iter: IntoIterator <Item = Cell>> (
+		iter: Iter,
+	) -> PackedLine {
+		PackedLine::default ().into_copy_of (iter)
+	}
+
+}
+
+impl IntoDefault for PackedLine {
+
+	fn into_default (self) -> PackedLine {
+		PackedLine {
+			len: 0,
+			can_fill: self.can_fill.into_default (),
+			can_empty: self.can_empty.into_default (),
+		}
+	}
+
+}
+
+#[ cfg (test) ]
+mod tests {
+
+	use std::ops::Deref;
+	use std::str::FromStr;
+
+	use super::*;
+
+	#[ test ]
+	fn test_packed_line_round_trip () {
+
+		let line = LineBuf::from_str (
+			"-# --#  ##-----#-------------# # # #-------------------------##---  -#",
+		).unwrap ();
+
+		let packed_line = PackedLine::from (line.deref ());
+
+		assert_eq! (packed_line.len (), line.len ());
+		assert_eq! (LineBuf::from (& packed_line), line);
+		assert_eq! (format! ("{:?}", packed_line), format! ("{:?}", line));
+
+	}
+
+	#[ test ]
+	fn test_packed_line_ranges () {
+
+		let line = LineBuf::from_str (& format! (
+			"{}#{} {}",
+			"-".repeat (60),
+			"-".repeat (70),
+			"-".repeat (10),
+		)).unwrap ();
+
+		let packed_line = PackedLine::from (line.deref ());
+
+		for start in 0 .. line.len () {
+			for end in start ..= line.len () {
+
+				assert_eq! (
+					packed_line.all_can_fill (start, end),
+					line [start .. end].iter ().all (Cell::can_fill),
+					"can fill {} .. {}",
+					start,
+					end,
+				);
+
+				assert_eq! (
+					packed_line.all_can_empty (start, end),
+					line [start .. end].iter ().all (Cell::can_empty),
+					"can empty {} .. {}",
+					start,
+					end,
+				);
+
+			}
+		}
+
+	}
+
+	#[ test ]
+	fn test_packed_line_set () {
+
+		let mut packed_line = PackedLine::with_size (3);
+
+		packed_line.set (0, Cell::FILLED);
+		packed_line.set (2, Cell::EMPTY);
+		packed_line.set (2, Cell::UNKNOWN);
+		packed_line.set (1, Cell::EMPTY);
+
+		assert_eq! (packed_line.to_line_buf (), LineBuf::from_str ("# -").unwrap ());
+
+	}
+
+}
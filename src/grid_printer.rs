@@ -1,5 +1,4 @@
 use std::io;
-use std::iter;
 
 use crate::data::*;
 
@@ -8,6 +7,8 @@ pub struct GridPrinter {
 	middle: Vec <(String, String)>,
 	bottom: Vec <String>,
 	shown: bool,
+	highlight: Option <(LineSize, LineSize)>,
+	palette: Vec <Colour>,
 }
 
 impl GridPrinter {
@@ -30,12 +31,15 @@ impl GridPrinter {
 
 			top.push (format! (
 				"{} {}\n",
-				iter::repeat ("  ").take (max_row_clues).collect::<String> (),
+				"  ".repeat (max_row_clues),
 				(0 .. clues.num_cols ()).map (|col_index| 
 					if (max_col_clues - row - 1) < clues.col (col_index).len () {
-						format! ("{:2}", clues.col (col_index) [
-							row - (max_col_clues - clues.col (col_index).len ())
-						])
+						format_clue (
+							clues,
+							clues.col (col_index),
+							clues.col_colours (col_index),
+							row - (max_col_clues - clues.col (col_index).len ()),
+						)
 					} else {
 						"  ".to_string ()
 					}
@@ -46,10 +50,8 @@ impl GridPrinter {
 
 		top.push (format! (
 			"{} ▄{}▄\n",
-			iter::repeat ("  ").take (max_row_clues).collect::<String> (),
-			iter::repeat ("▄▄").take (
-				clues.num_cols () as usize,
-			).collect::<String> (),
+			"  ".repeat (max_row_clues),
+			"▄▄".repeat (clues.num_cols () as usize),
 		));
 
 		for row_index in 0 .. clues.num_rows () {
@@ -57,11 +59,14 @@ impl GridPrinter {
 			middle.push ( (
 				format! (
 					"{}{} █",
-					iter::repeat ("  ").take (
-						max_row_clues - clues.row (row_index).len (),
-					).collect::<String> (),
-					clues.row (row_index).iter ().map (|clue|
-						format! ("{:2}", clue),
+					"  ".repeat (max_row_clues - clues.row (row_index).len ()),
+					(0 .. clues.row (row_index).len ()).map (|clue_index|
+						format_clue (
+							clues,
+							clues.row (row_index),
+							clues.row_colours (row_index),
+							clue_index,
+						),
 					).collect::<String> (),
 				),
 				String::new (),
@@ -71,10 +76,8 @@ impl GridPrinter {
 
 		bottom.push (format! (
 			"{} ▀{}▀\n",
-			iter::repeat ("  ").take (max_row_clues).collect::<String> (),
-			iter::repeat ("▀▀").take (
-				clues.num_cols () as usize,
-			).collect::<String> (),
+			"  ".repeat (max_row_clues),
+			"▀▀".repeat (clues.num_cols () as usize),
 		));
 
 		GridPrinter {
@@ -82,10 +85,19 @@ impl GridPrinter {
 			middle: middle,
 			bottom: bottom,
 			shown: false,
+			highlight: None,
+			palette: clues.palette ().to_vec (),
 		}
 
 	}
 
+	pub fn set_highlight (
+		& mut self,
+		highlight: Option <(LineSize, LineSize)>,
+	) {
+		self.highlight = highlight;
+	}
+
 	pub fn print (
 		& mut self,
 		writer: & mut dyn io::Write,
@@ -98,7 +110,21 @@ impl GridPrinter {
 
 			right.clear ();
 
-			for cell in grid.row (row_index) {
+			for (col_index, cell) in grid.row (row_index).enumerate () {
+
+				if self.highlight == Some ((row_index, col_index as LineSize)) {
+					right.push_str ("░░");
+					continue;
+				}
+
+				let colour = cell.colour_index ().and_then (
+					|index| self.palette.get (index as usize - 1),
+				);
+
+				if let Some (colour) = colour {
+					right.push_str (& format! ("{}██\x1b[39m", ansi_colour (colour)));
+					continue;
+				}
 
 				right.push_str (
 					match cell {
@@ -106,6 +132,7 @@ impl GridPrinter {
 						Cell::EMPTY => "██",
 						Cell::FILLED => "  ",
 						Cell::ERROR => "!!",
+						_ if ! cell.is_solved () => "▒▒",
 						_ => "??",
 					},
 				);
@@ -140,7 +167,7 @@ impl GridPrinter {
 			);
 
 			for _ in self.top.iter () {
-				write! (writer, "\n") ?;
+				writeln! (writer) ?;
 			}
 
 			for (left, right) in self.middle.iter () {
@@ -148,7 +175,7 @@ impl GridPrinter {
 			}
 
 			for _ in self.bottom.iter () {
-				write! (writer, "\n") ?;
+				writeln! (writer) ?;
 			}
 
 		}
@@ -159,3 +186,25 @@ impl GridPrinter {
 
 }
 
+fn format_clue (
+	clues: & Clues,
+	clues_line: & CluesLine,
+	colours: & [u8],
+	clue_index: usize,
+) -> String {
+
+	if ! clues.is_coloured () {
+		return format! ("{:2}", clues_line [clue_index]);
+	}
+
+	format! (
+		"{}{:2}\x1b[39m",
+		ansi_colour (& clues.palette () [colours [clue_index] as usize - 1]),
+		clues_line [clue_index],
+	)
+
+}
+
+fn ansi_colour (colour: & Colour) -> String {
+	format! ("\x1b[38;2;{};{};{}m", colour.red, colour.green, colour.blue)
+}
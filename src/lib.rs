@@ -1,4 +1,5 @@
 #![ allow (unused_parens) ]
+#![ allow (clippy::redundant_field_names) ]
 #![ warn (rust_2018_idioms) ]
 
 pub mod data;
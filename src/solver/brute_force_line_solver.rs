@@ -0,0 +1,354 @@
+use crate::data::*;
+use crate::solver::*;
+
+// reference line solver which enumerates every placement of the clues without
+// any caching or pruning and intersects them, for testing the other solvers
+
+#[ derive (Default) ]
+pub struct BruteForceLineSolver {
+	line: LineBuf,
+	placed: LineBuf,
+	solution: Option <LineBuf>,
+}
+
+impl BruteForceLineSolver {
+
+	pub fn new () -> BruteForceLineSolver {
+		Default::default ()
+	}
+
+	fn place (
+		& mut self,
+		clues_line: & [LineSize],
+		start: LineSize,
+	) {
+
+		// all clues placed, so the rest of the line is empty
+
+		if clues_line.is_empty () {
+
+			for cell_index in start .. self.line.len () {
+				self.placed [cell_index] = Cell::EMPTY;
+			}
+
+			self.intersect ();
+
+			return;
+
+		}
+
+		let size = clues_line [0];
+
+		let mut position = start;
+
+		while position + size <= self.line.len () {
+
+			for cell_index in start .. position {
+				self.placed [cell_index] = Cell::EMPTY;
+			}
+
+			for cell_index in position .. position + size {
+				self.placed [cell_index] = Cell::FILLED;
+			}
+
+			if position + size == self.line.len () {
+				if clues_line.len () == 1 {
+					self.intersect ();
+				}
+			} else {
+				self.placed [position + size] = Cell::EMPTY;
+				self.place (& clues_line [1 .. ], position + size + 1);
+			}
+
+			position += 1;
+
+		}
+
+	}
+
+	fn intersect (& mut self) {
+
+		let consistent = self.line.iter ().zip (self.placed.iter ()).all (
+			|(cell, placed_cell)| cell.is_unknown () || cell == placed_cell,
+		);
+
+		if ! consistent {
+			return;
+		}
+
+		match self.solution {
+
+			Some (ref mut solution) => {
+				for (cell, placed_cell) in solution.iter_mut ().zip (self.placed.iter ()) {
+					if * cell != placed_cell {
+						* cell = Cell::UNKNOWN;
+					}
+				}
+			},
+
+			None => {
+				self.solution = Some (self.placed.iter ().collect ());
+			},
+
+		}
+
+	}
+
+}
+
+impl LineSolve for BruteForceLineSolver {
+
+	fn solve_line <
+		LineIter: IntoIterator <Item = Cell>,
+	> (
+		& mut self,
+		line_iter: LineIter,
+		clues_line: & CluesLine,
+	) -> Option <& Line> {
+
+		self.line = line_iter.into_iter ().collect ();
+		self.placed = LineBuf::with_size (self.line.len ());
+		self.solution = None;
+
+		self.place (clues_line, 0);
+
+		self.solution.as_deref ()
+
+	}
+
+}
+
+#[ cfg (test) ]
+mod tests {
+
+	use std::ops::Deref;
+	use std::str::FromStr;
+
+	use super::*;
+
+	#[ test ]
+	fn test_brute_force_solve_line () {
+
+		let mut line_solver = BruteForceLineSolver::new ();
+
+		let cases: Vec <(& str, CluesLine, Option <& str>)> = vec! [
+			("----------", vec! [ 3, 2, 3 ], Some ("### ## ###")),
+			("----------", vec! [ 3, 4 ], Some ("--#---##--")),
+			("----# ----", vec! [ 3, 4 ], Some ("  ### ####")),
+			("-#- -#-", vec! [ 3 ], None),
+			("--#--", vec! [ ], None),
+			("-----", vec! [ ], Some ("     ")),
+		];
+
+		for (line, clues_line, expected) in cases {
+
+			assert_eq! (
+				line_solver.solve_line (
+					& LineBuf::from_str (line).unwrap (),
+					& clues_line,
+				),
+				expected.map (|expected| LineBuf::from_str (expected).unwrap ()).as_deref (),
+				"{:?} {:?}",
+				line,
+				clues_line,
+			);
+
+		}
+
+	}
+
+	// small deterministic xorshift generator, so failures are reproducible
+
+	struct Random {
+		state: u64,
+	}
+
+	impl Random {
+
+		fn next (& mut self) -> u64 {
+			self.state ^= self.state << 13;
+			self.state ^= self.state >> 7;
+			self.state ^= self.state << 17;
+			self.state
+		}
+
+		fn below (& mut self, limit: u64) -> u64 {
+			self.next () % limit
+		}
+
+	}
+
+	fn random_case (random: & mut Random) -> (LineBuf, CluesLine) {
+
+		let line_size = random.below (20) as LineSize;
+
+		// derive the clues from a random solution, so most cases are solvable
+
+		let solution: LineBuf = (0 .. line_size).map (
+			|_| if random.below (2) == 0 { Cell::EMPTY } else { Cell::FILLED },
+		).collect ();
+
+		let mut clues_line: CluesLine = Vec::new ();
+		let mut run = 0;
+
+		for cell in solution.iter () {
+			if cell.is_filled () {
+				run += 1;
+			} else if run > 0 {
+				clues_line.push (run);
+				run = 0;
+			}
+		}
+
+		if run > 0 {
+			clues_line.push (run);
+		}
+
+		// sometimes tweak a clue so there may be no solution
+
+		if ! clues_line.is_empty () && random.below (8) == 0 {
+			let index = random.below (clues_line.len () as u64) as usize;
+			clues_line [index] += 1;
+		}
+
+		// reveal some of the solution, and occasionally get a cell wrong
+
+		let line = solution.iter ().map (
+			|cell| match random.below (8) {
+				0 | 1 => cell,
+				2 if random.below (8) == 0 =>
+					if cell.is_filled () { Cell::EMPTY } else { Cell::FILLED },
+				_ => Cell::UNKNOWN,
+			},
+		).collect ();
+
+		(line, clues_line)
+
+	}
+
+	fn reference_solve (line: & Line, clues_line: & CluesLine) -> Option <LineBuf> {
+		BruteForceLineSolver::new ().solve_line (line, clues_line).map (ToOwned::to_owned)
+	}
+
+	fn line_solver_solve (line: & Line, clues_line: & CluesLine) -> Option <LineBuf> {
+		solve_line (line.iter (), clues_line).map (Iterator::collect)
+	}
+
+	fn packed_line_solver_solve (line: & Line, clues_line: & CluesLine) -> Option <LineBuf> {
+		LineSolver::with_packed_threshold (0).solve_line (line, clues_line).map (ToOwned::to_owned)
+	}
+
+	fn fast_line_solver_solve (line: & Line, clues_line: & CluesLine) -> Option <LineBuf> {
+		FastLineSolver::new ().solve_line (line, clues_line).map (ToOwned::to_owned)
+	}
+
+	fn mismatch (
+		solve: fn (& Line, & CluesLine) -> Option <LineBuf>,
+		line: & Line,
+		clues_line: & CluesLine,
+	) -> bool {
+		solve (line, clues_line) != reference_solve (line, clues_line)
+	}
+
+	// repeatedly simplify a failing case while it still fails
+
+	fn shrink (
+		solve: fn (& Line, & CluesLine) -> Option <LineBuf>,
+		mut line: LineBuf,
+		mut clues_line: CluesLine,
+	) -> (LineBuf, CluesLine) {
+
+		loop {
+
+			let mut candidates: Vec <(LineBuf, CluesLine)> = Vec::new ();
+
+			for index in 0 .. line.len () {
+
+				let mut cells = line.cells ().to_vec ();
+				cells.remove (index as usize);
+				candidates.push ((LineBuf::from (cells), clues_line.clone ()));
+
+				if ! line [index].is_unknown () {
+					let mut cells = line.cells ().to_vec ();
+					cells [index as usize] = Cell::UNKNOWN;
+					candidates.push ((LineBuf::from (cells), clues_line.clone ()));
+				}
+
+			}
+
+			for index in 0 .. clues_line.len () {
+
+				let mut smaller_clues = clues_line.clone ();
+				smaller_clues.remove (index);
+				candidates.push ((line.cells ().iter ().cloned ().collect (), smaller_clues));
+
+				if clues_line [index] > 1 {
+					let mut smaller_clues = clues_line.clone ();
+					smaller_clues [index] -= 1;
+					candidates.push ((line.cells ().iter ().cloned ().collect (), smaller_clues));
+				}
+
+			}
+
+			match candidates.into_iter ().find (
+				|(line, clues_line)| mismatch (solve, line, clues_line),
+			) {
+				Some ((smaller_line, smaller_clues)) => {
+					line = smaller_line;
+					clues_line = smaller_clues;
+				},
+				None => return (line, clues_line),
+			}
+
+		}
+
+	}
+
+	fn differential_test (
+		name: & str,
+		solve: fn (& Line, & CluesLine) -> Option <LineBuf>,
+	) {
+
+		let mut random = Random { state: 0x2545_f491_4f6c_dd1d };
+
+		for _ in 0 .. 5000 {
+
+			let (line, clues_line) = random_case (& mut random);
+
+			if ! mismatch (solve, & line, & clues_line) {
+				continue;
+			}
+
+			let (line, clues_line) = shrink (solve, line, clues_line);
+			let line_str = format! ("{:?}", line.deref ());
+
+			panic! (
+				"{} disagrees with reference for LineBuf::from_str (\"{}\") \
+				with clues {:?}: got {:?}, expected {:?}",
+				name,
+				& line_str [1 .. line_str.len () - 1],
+				clues_line,
+				solve (& line, & clues_line),
+				reference_solve (& line, & clues_line),
+			);
+
+		}
+
+	}
+
+	#[ test ]
+	fn test_line_solver_differential () {
+		differential_test ("LineSolver", line_solver_solve);
+	}
+
+	#[ test ]
+	fn test_packed_line_solver_differential () {
+		differential_test ("LineSolver (packed)", packed_line_solver_solve);
+	}
+
+	#[ test ]
+	fn test_fast_line_solver_differential () {
+		differential_test ("FastLineSolver", fast_line_solver_solve);
+	}
+
+}
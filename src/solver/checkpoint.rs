@@ -0,0 +1,612 @@
+use std::error::Error;
+use std::fs::File;
+use std::io;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::data::*;
+use crate::solver::*;
+use crate::solver::solve_grid::GuessFrame;
+use crate::solver::solve_grid::State;
+
+// bump this whenever the format changes, older checkpoints are then rejected
+// instead of being misread
+
+pub const CHECKPOINT_VERSION: u32 = 1;
+
+const CHECKPOINT_HEADER: & str = "nono checkpoint";
+
+// saves the clues, the backtracking and probing settings and the state of a
+// solve, apart from any proof being recorded and the work in progress on the
+// current line or probe, which are redone after loading
+
+pub fn save_checkpoint_file (
+	filename: & Path,
+	clues: & Clues,
+	snapshot: & GridSolverSnapshot,
+) -> Result <(), Box <dyn Error>> {
+
+	let mut file = io::BufWriter::new (File::create (filename) ?);
+
+	save_checkpoint (& mut file, clues, snapshot) ?;
+
+	file.flush () ?;
+
+	Ok (())
+
+}
+
+pub fn save_checkpoint (
+	writer: & mut dyn Write,
+	clues: & Clues,
+	snapshot: & GridSolverSnapshot,
+) -> io::Result <()> {
+
+	if clues.is_coloured () {
+		return Err (io::Error::new (
+			io::ErrorKind::Unsupported,
+			"checkpoints don't support coloured clues",
+		));
+	}
+
+	writeln! (writer, "{} {}", CHECKPOINT_HEADER, CHECKPOINT_VERSION) ?;
+
+	writeln! (writer, "size {} {}", clues.num_rows (), clues.num_cols ()) ?;
+
+	for clues_line in clues.rows () {
+		writeln! (writer, "row{}", format_numbers (clues_line.iter ())) ?;
+	}
+
+	for clues_line in clues.cols () {
+		writeln! (writer, "col{}", format_numbers (clues_line.iter ())) ?;
+	}
+
+	writeln! (writer, "backtracking {}", format_flags (& [ snapshot.backtracking ])) ?;
+	writeln! (writer, "probing {}", format_flags (& [ snapshot.probing ])) ?;
+
+	save_grid (writer, & snapshot.grid) ?;
+
+	// the current line is solved again after loading, so it counts as changed
+
+	let (mut changed_rows, mut changed_cols) = (
+		snapshot.changed_rows.clone (),
+		snapshot.changed_cols.clone (),
+	);
+
+	if let State::Solving (_) = snapshot.state {
+		if ! snapshot.vertical {
+			changed_rows [snapshot.index as usize] = true;
+		} else {
+			changed_cols [snapshot.index as usize] = true;
+		}
+	}
+
+	writeln! (writer, "changed-rows {}", format_flags (& changed_rows)) ?;
+	writeln! (writer, "changed-cols {}", format_flags (& changed_cols)) ?;
+	writeln! (writer, "row-changes{}", format_numbers (snapshot.row_changes.iter ())) ?;
+	writeln! (writer, "col-changes{}", format_numbers (snapshot.col_changes.iter ())) ?;
+
+	let stats = & snapshot.stats;
+
+	writeln! (
+		writer,
+		"stats{}",
+		format_numbers ([
+			stats.grid_iterations,
+			stats.line_iterations,
+			stats.guesses,
+			stats.backtracks,
+			stats.max_depth,
+			stats.probes,
+			stats.probed_cells,
+			stats.cache_hits,
+			stats.cache_misses,
+		].iter ()),
+	) ?;
+
+	writeln! (
+		writer,
+		"position {} {}",
+		if snapshot.vertical { "col" } else { "row" },
+		snapshot.index,
+	) ?;
+
+	// a line being solved is solved again from the start, as above, but a
+	// probe carries on from the cell it had reached, probing it again if its
+	// results weren't all applied yet
+
+	match snapshot.state {
+		State::Probing (row_index, col_index) =>
+			writeln! (writer, "state probing {} {}", row_index, col_index) ?,
+		State::Complete => writeln! (writer, "state complete") ?,
+		State::Contradiction => writeln! (writer, "state contradiction") ?,
+		State::Stuck => writeln! (writer, "state stuck") ?,
+		State::Scanning | State::Solving (_) => writeln! (writer, "state scanning") ?,
+	}
+
+	for frame in snapshot.guesses.iter () {
+		writeln! (writer, "guess {} {}", frame.row_index, frame.col_index) ?;
+		save_grid (writer, & frame.grid) ?;
+	}
+
+	writeln! (writer, "end") ?;
+
+	Ok (())
+
+}
+
+pub fn load_checkpoint_file (
+	filename: & Path,
+) -> Result <(Clues, GridSolverSnapshot), Box <dyn Error>> {
+
+	let mut file = File::open (
+		filename,
+	) ?;
+
+	load_checkpoint (& mut file)
+
+}
+
+pub fn load_checkpoint (
+	reader: & mut dyn io::Read,
+) -> Result <(Clues, GridSolverSnapshot), Box <dyn Error>> {
+
+	let mut lines = BufReader::new (reader).lines ();
+
+	let mut next_line = move || -> Result <String, Box <dyn Error>> {
+		match lines.next () {
+			Some (line) => Ok (line ?),
+			None => Err ("unexpected end of checkpoint".into ()),
+		}
+	};
+
+	// check the version before anything else
+
+	let header = next_line () ?;
+
+	let version = match header.strip_prefix (CHECKPOINT_HEADER) {
+		Some (version) => version.trim (),
+		None => return Err ("not a checkpoint file".into ()),
+	};
+
+	if version != CHECKPOINT_VERSION.to_string () {
+		return Err (format! (
+			"unsupported checkpoint version {}, expected {}",
+			version,
+			CHECKPOINT_VERSION,
+		).into ());
+	}
+
+	let size: Vec <LineSize> = parse_numbers (& next_line () ?, "size") ?;
+
+	if size.len () != 2 {
+		return Err ("invalid checkpoint size".into ());
+	}
+
+	let (num_rows, num_cols) = (size [0], size [1]);
+
+	let rows = (0 .. num_rows).map (
+		|_| parse_numbers (& next_line () ?, "row"),
+	).collect::<Result <Vec <CluesLine>, Box <dyn Error>>> () ?;
+
+	let cols = (0 .. num_cols).map (
+		|_| parse_numbers (& next_line () ?, "col"),
+	).collect::<Result <Vec <CluesLine>, Box <dyn Error>>> () ?;
+
+	let clues = Clues::new (rows, cols);
+
+	let backtracking = parse_flags (& next_line () ?, "backtracking", 1) ? [0];
+	let probing = parse_flags (& next_line () ?, "probing", 1) ? [0];
+
+	let grid = load_grid (& mut next_line, num_rows, num_cols) ?;
+
+	let changed_rows = parse_flags (& next_line () ?, "changed-rows", num_rows) ?;
+	let changed_cols = parse_flags (& next_line () ?, "changed-cols", num_cols) ?;
+
+	let row_changes: Vec <LineSize> = parse_numbers (& next_line () ?, "row-changes") ?;
+	let col_changes: Vec <LineSize> = parse_numbers (& next_line () ?, "col-changes") ?;
+
+	if row_changes.len () != num_rows as usize || col_changes.len () != num_cols as usize {
+		return Err ("invalid checkpoint change counts".into ());
+	}
+
+	let stats_values: Vec <usize> = parse_numbers (& next_line () ?, "stats") ?;
+
+	if stats_values.len () != 9 {
+		return Err ("invalid checkpoint stats".into ());
+	}
+
+	let stats = GridSolverStats {
+		grid_iterations: stats_values [0],
+		line_iterations: stats_values [1],
+		guesses: stats_values [2],
+		backtracks: stats_values [3],
+		max_depth: stats_values [4],
+		probes: stats_values [5],
+		probed_cells: stats_values [6],
+		cache_hits: stats_values [7],
+		cache_misses: stats_values [8],
+	};
+
+	let position_line = next_line () ?;
+
+	let (vertical, index) = match position_line.split (' ').collect::<Vec <& str>> () [..] {
+		[ "position", "row", index ] => (false, index.parse::<LineSize> () ?),
+		[ "position", "col", index ] => (true, index.parse::<LineSize> () ?),
+		_ => return Err ("invalid checkpoint position".into ()),
+	};
+
+	if index >= if vertical { num_cols } else { num_rows } {
+		return Err ("invalid checkpoint position".into ());
+	}
+
+	let state_line = next_line () ?;
+
+	let state = match state_line.split (' ').collect::<Vec <& str>> () [..] {
+		[ "state", "scanning" ] => State::Scanning,
+		[ "state", "probing", row_index, col_index ] => {
+			let (row_index, col_index) = (row_index.parse::<LineSize> () ?, col_index.parse::<LineSize> () ?);
+			if row_index > num_rows || col_index >= num_cols {
+				return Err ("invalid checkpoint state".into ());
+			}
+			State::Probing (row_index, col_index)
+		},
+		[ "state", "complete" ] => State::Complete,
+		[ "state", "contradiction" ] => State::Contradiction,
+		[ "state", "stuck" ] => State::Stuck,
+		_ => return Err ("invalid checkpoint state".into ()),
+	};
+
+	let mut guesses = Vec::new ();
+
+	loop {
+
+		let line = next_line () ?;
+
+		if line == "end" {
+			break;
+		}
+
+		let position: Vec <LineSize> = parse_numbers (& line, "guess") ?;
+
+		if position.len () != 2 || position [0] >= num_rows || position [1] >= num_cols {
+			return Err ("invalid checkpoint guess".into ());
+		}
+
+		guesses.push (GuessFrame {
+			grid: load_grid (& mut next_line, num_rows, num_cols) ?,
+			row_index: position [0],
+			col_index: position [1],
+		});
+
+	}
+
+	Ok ((clues, GridSolverSnapshot {
+		backtracking: backtracking,
+		probing: probing,
+		grid: grid,
+		stats: stats,
+		changed_rows: changed_rows,
+		changed_cols: changed_cols,
+		row_changes: row_changes,
+		col_changes: col_changes,
+		solved_line: Default::default (),
+		guesses: guesses,
+		probed: matches! (state, State::Probing (..)),
+		probed_cells: Vec::new (),
+		proof: None,
+		proof_step: 0,
+		state: state,
+		vertical: vertical,
+		index: index,
+		index_changed: true,
+	}))
+
+}
+
+fn save_grid (writer: & mut dyn Write, grid: & Grid) -> io::Result <()> {
+
+	for row_index in 0 .. grid.num_rows () {
+
+		writeln! (
+			writer,
+			"|{}|",
+			grid.row (row_index).map (
+				|cell| match cell {
+					Cell::EMPTY => '.',
+					Cell::FILLED => '#',
+					_ => '-',
+				},
+			).collect::<String> (),
+		) ?;
+
+	}
+
+	Ok (())
+
+}
+
+fn load_grid (
+	next_line: & mut dyn FnMut () -> Result <String, Box <dyn Error>>,
+	num_rows: LineSize,
+	num_cols: LineSize,
+) -> Result <Grid, Box <dyn Error>> {
+
+	let mut grid = Grid::new (num_rows, num_cols);
+
+	for row_index in 0 .. num_rows {
+
+		let line = next_line () ?;
+
+		let cells = match line.strip_prefix ('|').and_then (|line| line.strip_suffix ('|')) {
+			Some (cells) if cells.len () == num_cols as usize => cells,
+			_ => return Err ("invalid checkpoint grid".into ()),
+		};
+
+		for (col_index, ch) in cells.chars ().enumerate () {
+			grid [(row_index, col_index as LineSize)] = match ch {
+				'-' => Cell::UNKNOWN,
+				'.' => Cell::EMPTY,
+				'#' => Cell::FILLED,
+				_ => return Err ("invalid checkpoint grid".into ()),
+			};
+		}
+
+	}
+
+	Ok (grid)
+
+}
+
+fn format_numbers <Item: ToString, Iter: Iterator <Item = Item>> (iter: Iter) -> String {
+	iter.map (|item| format! (" {}", item.to_string ())).collect ()
+}
+
+fn format_flags (flags: & [bool]) -> String {
+	flags.iter ().map (|& flag| if flag { '1' } else { '0' }).collect ()
+}
+
+fn parse_numbers <Item: FromStr> (
+	line: & str,
+	keyword: & str,
+) -> Result <Vec <Item>, Box <dyn Error>> {
+
+	let mut words = line.split (' ');
+
+	if words.next () != Some (keyword) {
+		return Err (format! ("expected {} in checkpoint", keyword).into ());
+	}
+
+	words.map (
+		|word| word.parse::<Item> ().map_err (
+			|_| format! ("invalid number in checkpoint {}", keyword).into (),
+		),
+	).collect ()
+
+}
+
+fn parse_flags (
+	line: & str,
+	keyword: & str,
+	size: LineSize,
+) -> Result <Vec <bool>, Box <dyn Error>> {
+
+	let flags = match line.strip_prefix (keyword) {
+		Some (flags) => flags.trim (),
+		None => return Err (format! ("expected {} in checkpoint", keyword).into ()),
+	};
+
+	if flags.len () != size as usize {
+		return Err (format! ("invalid checkpoint {}", keyword).into ());
+	}
+
+	flags.chars ().map (
+		|ch| match ch {
+			'0' => Ok (false),
+			'1' => Ok (true),
+			_ => Err (format! ("invalid checkpoint {}", keyword).into ()),
+		},
+	).collect ()
+
+}
+
+#[ cfg (test) ]
+mod tests {
+
+	use super::*;
+
+	#[ test ]
+	fn test_checkpoint_round_trip () {
+
+		let clues = load_sample ("wikipedia");
+
+		let mut grid_solver = GridSolver::new (
+			unknown_grid (& clues),
+			clues.clone (),
+		).unwrap ();
+
+		for _ in 0 .. 100 {
+			grid_solver.next ().unwrap ();
+		}
+
+		let mut data = Vec::new ();
+		save_checkpoint (& mut data, & clues, & grid_solver.snapshot ()).unwrap ();
+
+		let (loaded_clues, snapshot) = load_checkpoint (& mut data.as_slice ()).unwrap ();
+
+		assert_eq! (format! ("{:?}", loaded_clues), format! ("{:?}", clues));
+		assert_eq! (snapshot.grid (), grid_solver.grid ());
+		assert_eq! (snapshot.stats ().line_iterations, grid_solver.stats ().line_iterations);
+
+		// resuming from the checkpoint gives the same solution
+
+		let mut resumed_solver = GridSolver::new (
+			unknown_grid (& loaded_clues),
+			loaded_clues,
+		).unwrap ();
+
+		resumed_solver.restore (snapshot);
+
+		finish (& mut grid_solver);
+		finish (& mut resumed_solver);
+
+		assert! (resumed_solver.is_solved ());
+		assert_eq! (resumed_solver.grid (), grid_solver.grid ());
+
+	}
+
+	#[ test ]
+	fn test_checkpoint_guesses () {
+
+		let clues = load_clues ("rows\n2\n1 1\n1\n1\ncols\n2\n2\n1\n1\n");
+
+		let mut grid_solver = GridSolver::with_config (
+			unknown_grid (& clues),
+			clues.clone (),
+			GridSolverConfig {
+				backtracking: true,
+				.. Default::default ()
+			},
+		).unwrap ();
+
+		while let Some (event) = grid_solver.next () {
+			if let GridSolverEvent::Guess (..) = event {
+				break;
+			}
+		}
+
+		assert_eq! (grid_solver.depth (), 1);
+
+		let mut data = Vec::new ();
+		save_checkpoint (& mut data, & clues, & grid_solver.snapshot ()).unwrap ();
+
+		let (_, snapshot) = load_checkpoint (& mut data.as_slice ()).unwrap ();
+		assert! (snapshot.backtracking ());
+		assert! (! snapshot.probing ());
+
+		// restoring keeps the resumed solver's own settings
+
+		let mut plain_solver = GridSolver::new (
+			unknown_grid (& clues),
+			clues.clone (),
+		).unwrap ();
+
+		plain_solver.restore (snapshot.clone ());
+
+		assert! (! plain_solver.config ().backtracking);
+
+		// so it is resumed with the saved ones merged in, as the cli does
+
+		let mut resumed_solver = GridSolver::with_config (
+			unknown_grid (& clues),
+			clues,
+			GridSolverConfig {
+				backtracking: snapshot.backtracking (),
+				probing: snapshot.probing (),
+				.. Default::default ()
+			},
+		).unwrap ();
+
+		resumed_solver.restore (snapshot);
+
+		assert_eq! (resumed_solver.depth (), 1);
+
+		finish (& mut grid_solver);
+		finish (& mut resumed_solver);
+
+		assert_eq! (resumed_solver.grid (), grid_solver.grid ());
+		assert_eq! (resumed_solver.stats ().backtracks, grid_solver.stats ().backtracks);
+
+	}
+
+	// a checkpoint saved part way through probing carries on from the same cell
+	// after loading
+
+	#[ test ]
+	fn test_checkpoint_probing () {
+
+		let clues = load_clues ("rows\n1\n3\n1 1\n1 1\n1 1\ncols\n1 2\n1 1\n1\n1\n3\n");
+
+		let config = GridSolverConfig {
+			probing: true,
+			.. Default::default ()
+		};
+
+		let mut grid_solver = GridSolver::with_config (
+			unknown_grid (& clues),
+			clues.clone (),
+			config.clone (),
+		).unwrap ();
+
+		while let Some (event) = grid_solver.next () {
+			if let GridSolverEvent::Probe (..) = event {
+				break;
+			}
+		}
+
+		assert! (grid_solver.is_probing ());
+
+		let mut data = Vec::new ();
+		save_checkpoint (& mut data, & clues, & grid_solver.snapshot ()).unwrap ();
+		assert! (String::from_utf8_lossy (& data).contains ("\nstate probing "));
+
+		let (_, snapshot) = load_checkpoint (& mut data.as_slice ()).unwrap ();
+
+		let mut resumed_solver = GridSolver::with_config (
+			unknown_grid (& clues),
+			clues,
+			config,
+		).unwrap ();
+
+		resumed_solver.restore (snapshot);
+
+		assert! (resumed_solver.is_probing ());
+
+		finish (& mut grid_solver);
+		finish (& mut resumed_solver);
+
+		assert! (resumed_solver.is_solved ());
+		assert_eq! (resumed_solver.grid (), grid_solver.grid ());
+		assert_eq! (resumed_solver.stats ().guesses, 0);
+
+	}
+
+	#[ test ]
+	fn test_checkpoint_version () {
+
+		let error = load_checkpoint (
+			& mut "nono checkpoint 0\nsize 1 1\n".as_bytes (),
+		).unwrap_err ();
+
+		assert_eq! (
+			error.to_string (),
+			format! ("unsupported checkpoint version 0, expected {}", CHECKPOINT_VERSION),
+		);
+
+		assert! (load_checkpoint (& mut "rows\n1\ncols\n1\n".as_bytes ()).is_err ());
+
+	}
+
+	#[ test ]
+	fn test_checkpoint_truncated () {
+
+		let clues = load_sample ("heart");
+
+		let grid_solver = GridSolver::new (
+			unknown_grid (& clues),
+			clues.clone (),
+		).unwrap ();
+
+		let mut data = Vec::new ();
+		save_checkpoint (& mut data, & clues, & grid_solver.snapshot ()).unwrap ();
+
+		for size in 0 .. data.len () - 4 {
+			assert! (load_checkpoint (& mut & data [0 .. size]).is_err (), "{}", size);
+		}
+
+	}
+
+}
@@ -7,6 +7,25 @@ pub fn place_clue (
 
 	CluePlacer::new (
 		line,
+		None,
+		size,
+	)
+
+}
+
+// as above but checks runs of cells using the packed copy of the line, which
+// must start at the same cell as the line slice
+
+pub fn place_clue_packed <'a> (
+	line: & 'a Line,
+	packed_line: & 'a PackedLine,
+	offset: LineSize,
+	size: LineSize,
+) -> CluePlacer <'a> {
+
+	CluePlacer::new (
+		line,
+		Some ((packed_line, offset)),
 		size,
 	)
 
@@ -14,6 +33,7 @@ pub fn place_clue (
 
 pub struct CluePlacer <'a> {
 	line: & 'a Line,
+	packed_line: Option <(& 'a PackedLine, LineSize)>,
 	size: LineSize,
 	start: LineSize,
 }
@@ -21,18 +41,39 @@ pub struct CluePlacer <'a> {
 impl <'a> CluePlacer <'a> {
 
 	fn new (
-		line: & Line,
+		line: & 'a Line,
+		packed_line: Option <(& 'a PackedLine, LineSize)>,
 		size: LineSize,
-	) -> CluePlacer <'_> {
+	) -> CluePlacer <'a> {
 
 		CluePlacer {
 			line: line,
+			packed_line: packed_line,
 			size: size,
 			start: 0,
 		}
 
 	}
 
+	fn can_fill (& self) -> bool {
+
+		match self.packed_line {
+
+			Some ((packed_line, offset)) => packed_line.all_can_fill (
+				offset + self.start,
+				offset + self.start + self.size,
+			),
+
+			None => self.line.iter ().skip (
+				self.start as usize,
+			).take (
+				self.size as usize,
+			).all (Cell::can_fill),
+
+		}
+
+	}
+
 }
 
 impl <'a> Iterator for CluePlacer <'a> {
@@ -53,12 +94,8 @@ impl <'a> Iterator for CluePlacer <'a> {
 				return None;
 			}
 
-			let result = if self.line.iter ().skip (
-				self.start as usize,
-			).take (
-				self.size as usize,
-			).all (Cell::can_fill) && (false
-				|| self.line.len () == self.start + self.size
+			let result = if self.can_fill () && (
+				self.line.len () == self.start + self.size
 				|| self.line [self.start + self.size].can_empty ()
 			) {
 				Some (self.start)
@@ -81,6 +118,9 @@ impl <'a> Iterator for CluePlacer <'a> {
 #[ cfg (test) ]
 mod tests {
 
+	use std::ops::Deref;
+	use std::str::FromStr;
+
 	use super::*;
 
 	#[ test ]
@@ -109,5 +149,27 @@ mod tests {
 
 	}
 
+
+	#[ test ]
+	fn test_place_clue_packed () {
+
+		let line = LineBuf::from_str ("--- -#----").unwrap ();
+		let packed_line = PackedLine::from (line.deref ());
+
+		assert_eq! (
+			place_clue_packed (
+				& line [3 .. ],
+				& packed_line,
+				3,
+				2,
+			).collect::<Vec <LineSize>> (),
+			place_clue (
+				& line [3 .. ],
+				2,
+			).collect::<Vec <LineSize>> (),
+		);
+
+	}
+
 }
 
@@ -16,6 +16,7 @@ pub struct CluesPlacer <'a> {
 	cache: Cache,
 	stack: Vec <Frame <'a>>,
 	line: & 'a Line,
+	packed_line: Option <& 'a PackedLine>,
 	clues: & 'a [LineSize],
 	started: bool,
 }
@@ -34,26 +35,26 @@ impl <'a> CluesPlacer <'a> {
 		clues: & 'a [LineSize],
 	) -> CluesPlacer <'a> {
 
-println! ("CluesPlacer::new");
-
 		CluesPlacer {
 			cache: Cache::new (clues.len (), line.len ()),
 			stack: Vec::with_capacity (clues.len ()),
 			line: line,
+			packed_line: None,
 			clues: clues,
 			started: false,
 		}
 
 	}
 
-	pub fn into_default <'b> (
+	pub fn into_default (
 		self,
 	) -> CluesPlacer <'static> {
 
 		CluesPlacer {
 			cache: self.cache,
-			stack: unsafe { mem::transmute (self.stack.into_default ()) },
+			stack: unsafe { mem::transmute::<Vec <Frame <'_>>, Vec <Frame <'static>>> (self.stack.into_default ()) },
 			line: Default::default (),
+			packed_line: None,
 			clues: Default::default (),
 			started: false,
 		}
@@ -72,12 +73,30 @@ println! ("CluesPlacer::new");
 			cache: copy.cache.into_new (clues.len (), line.len ()),
 			stack: copy.stack,
 			line: line,
+			packed_line: None,
 			clues: clues,
 			started: false,
 		}
 
 	}
 
+	// the packed line must hold the same cells as the line, and is used to
+	// check runs of cells a word at a time
+
+	pub fn into_new_packed <'b> (
+		self,
+		line: & 'b Line,
+		packed_line: & 'b PackedLine,
+		clues: & 'b [LineSize],
+	) -> CluesPlacer <'b> {
+
+		CluesPlacer {
+			packed_line: Some (packed_line),
+			.. self.into_new (line, clues)
+		}
+
+	}
+
 	pub fn advance (& mut self) -> bool {
 
 		if ! self.started {
@@ -147,7 +166,10 @@ println! ("CluesPlacer::new");
 	) -> bool {
 
 		if self.clues.len () == depth {
-			return self.line.iter ().skip (offset as usize).all (Cell::can_empty);
+			return match self.packed_line {
+				Some (packed_line) => packed_line.all_can_empty (offset, self.line.len ()),
+				None => self.line.iter ().skip (offset as usize).all (Cell::can_empty),
+			};
 		}
 
 		if offset + self.clues [depth] > self.line.len () {
@@ -158,10 +180,18 @@ println! ("CluesPlacer::new");
 			return false;
 		}
 
-		let clue_placer = place_clue (
-			& self.line [offset .. ],
-			self.clues [depth],
-		);
+		let clue_placer = match self.packed_line {
+			Some (packed_line) => place_clue_packed (
+				& self.line [offset .. ],
+				packed_line,
+				offset,
+				self.clues [depth],
+			),
+			None => place_clue (
+				& self.line [offset .. ],
+				self.clues [depth],
+			),
+		};
 
 		self.stack.push (Frame {
 			offset: offset,
@@ -272,8 +302,11 @@ impl Cache {
 }
 
 #[ cfg (test) ]
+#[ allow (clippy::useless_vec) ]
 mod tests {
 
+	use std::str::FromStr;
+
 	use super::*;
 
 	#[ test ]
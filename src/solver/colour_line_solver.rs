@@ -0,0 +1,511 @@
+use std::mem;
+
+use crate::data::*;
+use crate::solver::*;
+
+// line solver for puzzles with coloured clues, using the same dynamic
+// programming as the fast line solver, except that adjacent clues only need a
+// gap between them when they are the same colour
+
+#[ derive (Default) ]
+pub struct ColourLineSolver {
+	line: LineBuf,
+	colours: Vec <u8>,
+	num_clues: usize,
+	num_colours: usize,
+	line_size: usize,
+	not_colourable: Vec <usize>,
+	prefix: Vec <bool>,
+	suffix: Vec <bool>,
+	colour_counts: Vec <isize>,
+	can_empty: Vec <bool>,
+	counts: Vec <isize>,
+}
+
+impl ColourLineSolver {
+
+	pub fn new () -> ColourLineSolver {
+		Default::default ()
+	}
+
+	// solves the line for clues of the given colours, numbered from one
+
+	pub fn solve_colour_line <
+		LineIter: IntoIterator <Item = Cell>,
+	> (
+		& mut self,
+		line_iter: LineIter,
+		clues_line: & CluesLine,
+		colours: & [u8],
+	) -> Option <& Line> {
+
+		assert_eq! (clues_line.len (), colours.len ());
+
+		self.colours.clear ();
+		self.colours.extend_from_slice (colours);
+
+		self.solve (line_iter, clues_line)
+
+	}
+
+	fn solve <
+		LineIter: IntoIterator <Item = Cell>,
+	> (
+		& mut self,
+		line_iter: LineIter,
+		clues_line: & CluesLine,
+	) -> Option <& Line> {
+
+		let line = mem::take (& mut self.line);
+		self.line = line.into_copy_of (line_iter);
+
+		self.reset (clues_line);
+		self.fill_prefix (clues_line);
+
+		if ! self.prefix [self.table_index (self.num_clues, self.line_size)] {
+			return None;
+		}
+
+		self.fill_suffix (clues_line);
+		self.fill_placements (clues_line);
+
+		let mut counts = mem::take (& mut self.counts);
+		counts.clear ();
+		counts.resize (self.num_colours + 1, 0);
+
+		let mut solved = true;
+
+		for cell_index in 0 .. self.line_size {
+
+			let mut cell = if self.can_empty [cell_index] {
+				Cell::EMPTY
+			} else {
+				Cell::ERROR
+			};
+
+			for (colour, count) in counts.iter_mut ().enumerate ().skip (1) {
+				* count += self.colour_counts [self.count_index (colour, cell_index)];
+				if * count > 0 {
+					cell = cell.union (Cell::colour (colour as u8));
+				}
+			}
+
+			if cell.is_error () {
+				solved = false;
+				break;
+			}
+
+			self.line [cell_index as LineSize] = cell;
+
+		}
+
+		self.counts = counts;
+
+		if ! solved {
+			return None;
+		}
+
+		Some (& self.line)
+
+	}
+
+	fn reset (& mut self, clues_line: & CluesLine) {
+
+		self.num_clues = clues_line.len ();
+		self.num_colours = self.colours.iter ().copied ().max ().unwrap_or (0) as usize;
+		self.line_size = self.line.len () as usize;
+
+		let table_size = (self.num_clues + 1) * (self.line_size + 2);
+		let count_size = (self.num_colours + 1) * (self.line_size + 1);
+
+		// cumulative counts of cells which can't be each colour
+
+		self.not_colourable.clear ();
+		self.not_colourable.resize (count_size, 0);
+
+		for colour in 1 ..= self.num_colours {
+			for cell_index in 0 .. self.line_size {
+				let last = self.not_colourable [self.count_index (colour, cell_index)];
+				let index = self.count_index (colour, cell_index + 1);
+				self.not_colourable [index] = if self.line [cell_index as LineSize].can_colour (colour as u8) {
+					last
+				} else {
+					last + 1
+				};
+			}
+		}
+
+		self.prefix.clear ();
+		self.prefix.resize (table_size, false);
+
+		self.suffix.clear ();
+		self.suffix.resize (table_size, false);
+
+		self.colour_counts.clear ();
+		self.colour_counts.resize (count_size, 0);
+
+		self.can_empty.clear ();
+		self.can_empty.resize (self.line_size, false);
+
+	}
+
+	fn table_index (& self, clue_index: usize, cell_index: usize) -> usize {
+		clue_index * (self.line_size + 2) + cell_index
+	}
+
+	fn count_index (& self, colour: usize, cell_index: usize) -> usize {
+		colour * (self.line_size + 1) + cell_index
+	}
+
+	// true if the cells [start, end) can all be the colour of the clue
+
+	fn colourable (& self, clue_index: usize, start: usize, end: usize) -> bool {
+		let colour = self.colours [clue_index] as usize;
+		self.not_colourable [self.count_index (colour, end)]
+			== self.not_colourable [self.count_index (colour, start)]
+	}
+
+	fn cell_can_empty (& self, cell_index: usize) -> bool {
+		self.line [cell_index as LineSize].can_empty ()
+	}
+
+	// true if the clues before clue_index can be placed before a block of it
+	// starting at start, with a gap only if the previous clue is the same colour
+
+	fn fits_before (& self, clue_index: usize, start: usize) -> bool {
+
+		if clue_index > 0 && self.colours [clue_index - 1] == self.colours [clue_index] {
+			start > 0
+				&& self.cell_can_empty (start - 1)
+				&& self.prefix [self.table_index (clue_index, start - 1)]
+		} else {
+			self.prefix [self.table_index (clue_index, start)]
+		}
+
+	}
+
+	// true if the clues after clue_index can be placed after a block of it
+	// ending at end
+
+	fn fits_after (& self, clue_index: usize, end: usize) -> bool {
+
+		if clue_index + 1 < self.num_clues
+		&& self.colours [clue_index + 1] == self.colours [clue_index] {
+			end < self.line_size
+				&& self.cell_can_empty (end)
+				&& self.suffix [self.table_index (clue_index + 1, end + 1)]
+		} else {
+			self.suffix [self.table_index (clue_index + 1, end)]
+		}
+
+	}
+
+	// prefix [clue_index, cell_index] is true if the first clue_index clues can
+	// be placed in the first cell_index cells, with the rest of them empty
+
+	fn fill_prefix (& mut self, clues_line: & CluesLine) {
+
+		let index = self.table_index (0, 0);
+		self.prefix [index] = true;
+
+		for clue_index in 0 ..= self.num_clues {
+			for cell_index in 1 ..= self.line_size {
+
+				let mut value = self.cell_can_empty (cell_index - 1)
+					&& self.prefix [self.table_index (clue_index, cell_index - 1)];
+
+				if ! value && clue_index > 0 {
+
+					let size = clues_line [clue_index - 1] as usize;
+
+					value = cell_index >= size
+						&& self.colourable (clue_index - 1, cell_index - size, cell_index)
+						&& self.fits_before (clue_index - 1, cell_index - size);
+
+				}
+
+				let index = self.table_index (clue_index, cell_index);
+				self.prefix [index] = value;
+
+			}
+		}
+
+	}
+
+	// suffix [clue_index, cell_index] is true if the clues from clue_index
+	// onwards can be placed in the cells from cell_index onwards
+
+	fn fill_suffix (& mut self, clues_line: & CluesLine) {
+
+		let index = self.table_index (self.num_clues, self.line_size);
+		self.suffix [index] = true;
+
+		for clue_index in (0 ..= self.num_clues).rev () {
+			for cell_index in (0 .. self.line_size).rev () {
+
+				let mut value = self.cell_can_empty (cell_index)
+					&& self.suffix [self.table_index (clue_index, cell_index + 1)];
+
+				if ! value && clue_index < self.num_clues {
+
+					let end = cell_index + clues_line [clue_index] as usize;
+
+					value = end <= self.line_size
+						&& self.colourable (clue_index, cell_index, end)
+						&& self.fits_after (clue_index, end);
+
+				}
+
+				let index = self.table_index (clue_index, cell_index);
+				self.suffix [index] = value;
+
+			}
+		}
+
+	}
+
+	fn fill_placements (& mut self, clues_line: & CluesLine) {
+
+		// a cell can be empty if it falls between placed clues
+
+		for cell_index in 0 .. self.line_size {
+
+			if ! self.cell_can_empty (cell_index) {
+				continue;
+			}
+
+			self.can_empty [cell_index] = (0 ..= self.num_clues).any (
+				|clue_index|
+					self.prefix [self.table_index (clue_index, cell_index)]
+					&& self.suffix [self.table_index (clue_index, cell_index + 1)],
+			);
+
+		}
+
+		// a cell can be a colour if it is covered by a valid placement of a clue
+		// of that colour
+
+		for (clue_index, & size) in clues_line.iter ().enumerate () {
+
+			let size = size as usize;
+			let colour = self.colours [clue_index] as usize;
+
+			for start in 0 ..= self.line_size.saturating_sub (size) {
+
+				let end = start + size;
+
+				if self.colourable (clue_index, start, end)
+				&& self.fits_before (clue_index, start)
+				&& self.fits_after (clue_index, end) {
+					let start_index = self.count_index (colour, start);
+					let end_index = self.count_index (colour, end);
+					self.colour_counts [start_index] += 1;
+					self.colour_counts [end_index] -= 1;
+				}
+
+			}
+
+		}
+
+	}
+
+}
+
+// solves monochrome lines, where every clue is colour one
+
+impl LineSolve for ColourLineSolver {
+
+	fn solve_line <
+		LineIter: IntoIterator <Item = Cell>,
+	> (
+		& mut self,
+		line_iter: LineIter,
+		clues_line: & CluesLine,
+	) -> Option <& Line> {
+
+		self.colours.clear ();
+		self.colours.resize (clues_line.len (), 1);
+
+		self.solve (line_iter, clues_line)
+
+	}
+
+}
+
+#[ cfg (test) ]
+mod tests {
+
+	use super::*;
+
+	fn colour_line (source: & str) -> LineBuf {
+		source.chars ().map (
+			|ch| match ch {
+				'-' => Cell::unknown (2),
+				' ' => Cell::EMPTY,
+				_ => Cell::colour (ch.to_digit (10).unwrap () as u8),
+			},
+		).collect ()
+	}
+
+	#[ test ]
+	fn test_colour_solve_line () {
+
+		let cases: Vec <(& str, CluesLine, Vec <u8>, Option <& str>)> = vec! [
+
+			// different colours can touch, so these fill the line exactly
+
+			("-----", vec! [ 3, 2 ], vec! [ 1, 2 ], Some ("11122")),
+
+			// the same colours need a gap
+
+			("------", vec! [ 3, 2 ], vec! [ 1, 1 ], Some ("111 11")),
+			("-----", vec! [ 3, 2 ], vec! [ 1, 1 ], None),
+
+			// a known colour limits which clue can cover the cell
+
+			("---2-", vec! [ 3, 1 ], vec! [ 1, 2 ], Some ("1112 ")),
+			("--2--", vec! [ 1, 1 ], vec! [ 1, 1 ], None),
+
+			("", vec! [ ], vec! [ ], Some ("")),
+
+		];
+
+		let mut line_solver = ColourLineSolver::new ();
+
+		for (line, clues_line, colours, expected) in cases {
+
+			assert_eq! (
+				line_solver.solve_colour_line (
+					& colour_line (line),
+					& clues_line,
+					& colours,
+				).map (|line| line.to_owned ()),
+				expected.map (colour_line),
+				"{:?} {:?} {:?}",
+				line,
+				clues_line,
+				colours,
+			);
+
+		}
+
+	}
+
+	#[ test ]
+	fn test_colour_solve_line_partial () {
+
+		let mut line_solver = ColourLineSolver::new ();
+
+		// cells are left with every colour they could still be
+
+		let line = line_solver.solve_colour_line (
+			& colour_line ("----"),
+			& vec! [ 2, 1 ],
+			& [ 1, 2 ],
+		).unwrap ().to_owned ();
+
+		assert_eq! (line [0], Cell::EMPTY.union (Cell::colour (1)));
+		assert_eq! (line [1], Cell::colour (1));
+		assert_eq! (line [2], Cell::unknown (2));
+		assert_eq! (line [3], Cell::EMPTY.union (Cell::colour (2)));
+
+	}
+
+	// every solution must still be possible after solving, compared with
+	// brute force over all lines of two colours up to length 6
+
+	#[ test ]
+	fn test_colour_solve_line_exhaustive () {
+
+		let mut line_solver = ColourLineSolver::new ();
+
+		for line_size in 0 .. 7usize {
+
+			let num_lines = 3usize.pow (line_size as u32);
+
+			let solutions: Vec <Vec <u8>> = (0 .. num_lines).map (
+				|mut code| (0 .. line_size).map (|_| {
+					let value = (code % 3) as u8;
+					code /= 3;
+					value
+				}).collect (),
+			).collect ();
+
+			for solution in solutions.iter () {
+
+				let (clues_line, colours) = colour_clues (solution);
+
+				let matches: Vec <& Vec <u8>> = solutions.iter ().filter (
+					|other| colour_clues (other) == (clues_line.clone (), colours.clone ()),
+				).collect ();
+
+				let line = line_solver.solve_colour_line (
+					& colour_line (& "-".repeat (line_size)),
+					& clues_line,
+					& colours,
+				).unwrap ().to_owned ();
+
+				for cell_index in 0 .. line_size {
+
+					let expected = matches.iter ().fold (Cell::ERROR, |cell, other|
+						cell.union (match other [cell_index] {
+							0 => Cell::EMPTY,
+							colour => Cell::colour (colour),
+						}),
+					);
+
+					assert_eq! (line [cell_index as LineSize], expected, "{:?}", solution);
+
+				}
+
+			}
+
+		}
+
+	}
+
+	fn colour_clues (solution: & [u8]) -> (CluesLine, Vec <u8>) {
+
+		let mut clues_line = Vec::new ();
+		let mut colours = Vec::new ();
+
+		for (index, & colour) in solution.iter ().enumerate () {
+			if colour == 0 {
+				continue;
+			}
+			if index > 0 && solution [index - 1] == colour {
+				* clues_line.last_mut ().unwrap () += 1;
+			} else {
+				clues_line.push (1);
+				colours.push (colour);
+			}
+		}
+
+		(clues_line, colours)
+
+	}
+
+	#[ test ]
+	fn test_colour_line_solver_monochrome () {
+
+		let clues = load_sample ("wikipedia");
+
+		let grid = unknown_grid (& clues);
+
+		let mut grid_solver = GridSolver::new (grid.clone (), clues.clone ()).unwrap ();
+		finish (& mut grid_solver);
+
+		let mut colour_grid_solver = GridSolver::with_line_solver (
+			grid,
+			clues,
+			Default::default (),
+			ColourLineSolver::new (),
+		).unwrap ();
+		finish (& mut colour_grid_solver);
+
+		assert! (colour_grid_solver.is_solved ());
+		assert_eq! (colour_grid_solver.grid (), grid_solver.grid ());
+
+	}
+
+}
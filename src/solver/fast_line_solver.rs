@@ -0,0 +1,348 @@
+use std::mem;
+
+use crate::data::*;
+use crate::solver::*;
+
+// line solver which works out which cells can be filled or empty by dynamic
+// programming over (clue index, cell index), in O (line size * clues)
+
+#[ derive (Default) ]
+pub struct FastLineSolver {
+	line: LineBuf,
+	num_clues: usize,
+	line_size: usize,
+	not_fillable: Vec <usize>,
+	prefix: Vec <bool>,
+	suffix: Vec <bool>,
+	fill_counts: Vec <isize>,
+	can_empty: Vec <bool>,
+}
+
+impl FastLineSolver {
+
+	pub fn new () -> FastLineSolver {
+		Default::default ()
+	}
+
+	fn reset (& mut self, clues_line: & CluesLine) {
+
+		self.num_clues = clues_line.len ();
+		self.line_size = self.line.len () as usize;
+
+		let table_size = (self.num_clues + 1) * (self.line_size + 2);
+
+		self.not_fillable.clear ();
+		self.not_fillable.push (0);
+
+		for cell in self.line.iter () {
+			let last = * self.not_fillable.last ().unwrap ();
+			self.not_fillable.push (if cell.can_fill () { last } else { last + 1 });
+		}
+
+		self.prefix.clear ();
+		self.prefix.resize (table_size, false);
+
+		self.suffix.clear ();
+		self.suffix.resize (table_size, false);
+
+		self.fill_counts.clear ();
+		self.fill_counts.resize (self.line_size + 1, 0);
+
+		self.can_empty.clear ();
+		self.can_empty.resize (self.line_size, false);
+
+	}
+
+	fn table_index (& self, clue_index: usize, cell_index: usize) -> usize {
+		clue_index * (self.line_size + 2) + cell_index
+	}
+
+	// true if the cells [start, end) can all be filled
+
+	fn fillable (& self, start: usize, end: usize) -> bool {
+		self.not_fillable [end] == self.not_fillable [start]
+	}
+
+	fn cell_can_empty (& self, cell_index: usize) -> bool {
+		self.line [cell_index as LineSize].can_empty ()
+	}
+
+	// prefix [clue_index, cell_index] is true if the first clue_index clues can
+	// be placed in the first cell_index cells, with the rest of them empty
+
+	fn fill_prefix (& mut self, clues_line: & CluesLine) {
+
+		let index = self.table_index (0, 0);
+		self.prefix [index] = true;
+
+		for clue_index in 0 ..= self.num_clues {
+			for cell_index in 1 ..= self.line_size {
+
+				let mut value = self.cell_can_empty (cell_index - 1)
+					&& self.prefix [self.table_index (clue_index, cell_index - 1)];
+
+				if ! value && clue_index > 0 {
+
+					let size = clues_line [clue_index - 1] as usize;
+
+					value = cell_index >= size
+						&& self.fillable (cell_index - size, cell_index)
+						&& if cell_index == size {
+							clue_index == 1
+						} else {
+							self.cell_can_empty (cell_index - size - 1)
+								&& self.prefix [
+									self.table_index (clue_index - 1, cell_index - size - 1)
+								]
+						};
+
+				}
+
+				let index = self.table_index (clue_index, cell_index);
+				self.prefix [index] = value;
+
+			}
+		}
+
+	}
+
+	// suffix [clue_index, cell_index] is true if the clues from clue_index
+	// onwards can be placed in the cells from cell_index onwards
+
+	fn fill_suffix (& mut self, clues_line: & CluesLine) {
+
+		let index = self.table_index (self.num_clues, self.line_size);
+		self.suffix [index] = true;
+
+		for clue_index in (0 ..= self.num_clues).rev () {
+			for cell_index in (0 .. self.line_size).rev () {
+
+				let mut value = self.cell_can_empty (cell_index)
+					&& self.suffix [self.table_index (clue_index, cell_index + 1)];
+
+				if ! value && clue_index < self.num_clues {
+
+					let size = clues_line [clue_index] as usize;
+					let end = cell_index + size;
+
+					value = end <= self.line_size
+						&& self.fillable (cell_index, end)
+						&& if end == self.line_size {
+							clue_index + 1 == self.num_clues
+						} else {
+							self.cell_can_empty (end)
+								&& self.suffix [self.table_index (clue_index + 1, end + 1)]
+						};
+
+				}
+
+				let index = self.table_index (clue_index, cell_index);
+				self.suffix [index] = value;
+
+			}
+		}
+
+	}
+
+	fn fill_placements (& mut self, clues_line: & CluesLine) {
+
+		// a cell can be empty if it falls in a gap between placed clues
+
+		for cell_index in 0 .. self.line_size {
+
+			if ! self.cell_can_empty (cell_index) {
+				continue;
+			}
+
+			self.can_empty [cell_index] = (0 ..= self.num_clues).any (
+				|clue_index|
+					self.prefix [self.table_index (clue_index, cell_index)]
+					&& self.suffix [self.table_index (clue_index, cell_index + 1)],
+			);
+
+		}
+
+		// a cell can be filled if it is covered by any valid clue placement
+
+		for (clue_index, & size) in clues_line.iter ().enumerate () {
+
+			let size = size as usize;
+
+			for start in 0 ..= self.line_size.saturating_sub (size) {
+
+				let end = start + size;
+
+				if ! self.fillable (start, end) {
+					continue;
+				}
+
+				let before = if start == 0 {
+					clue_index == 0
+				} else {
+					self.cell_can_empty (start - 1)
+						&& self.prefix [self.table_index (clue_index, start - 1)]
+				};
+
+				let after = if end == self.line_size {
+					clue_index + 1 == self.num_clues
+				} else {
+					self.cell_can_empty (end)
+						&& self.suffix [self.table_index (clue_index + 1, end + 1)]
+				};
+
+				if before && after {
+					self.fill_counts [start] += 1;
+					self.fill_counts [end] -= 1;
+				}
+
+			}
+
+		}
+
+	}
+
+}
+
+impl LineSolve for FastLineSolver {
+
+	fn solve_line <
+		LineIter: IntoIterator <Item = Cell>,
+	> (
+		& mut self,
+		line_iter: LineIter,
+		clues_line: & CluesLine,
+	) -> Option <& Line> {
+
+		let line = mem::take (& mut self.line);
+		self.line = line.into_copy_of (line_iter);
+
+		self.reset (clues_line);
+		self.fill_prefix (clues_line);
+
+		if ! self.prefix [self.table_index (self.num_clues, self.line_size)] {
+			return None;
+		}
+
+		self.fill_suffix (clues_line);
+		self.fill_placements (clues_line);
+
+		let mut fill_count = 0;
+
+		for cell_index in 0 .. self.line_size {
+
+			fill_count += self.fill_counts [cell_index];
+
+			self.line [cell_index as LineSize] = match (
+				fill_count > 0,
+				self.can_empty [cell_index],
+			) {
+				(true, true) => Cell::UNKNOWN,
+				(true, false) => Cell::FILLED,
+				(false, true) => Cell::EMPTY,
+				(false, false) => return None,
+			};
+
+		}
+
+		Some (& self.line)
+
+	}
+
+}
+
+#[ cfg (test) ]
+mod tests {
+
+	use std::ops::Deref;
+	use std::str::FromStr;
+
+	use super::*;
+
+	fn fast_solve_line (line: & str, clues_line: & CluesLine) -> Option <LineBuf> {
+
+		let mut line_solver = FastLineSolver::new ();
+
+		line_solver.solve_line (
+			& LineBuf::from_str (line).unwrap (),
+			clues_line,
+		).map (|line| line.to_owned ())
+
+	}
+
+	#[ test ]
+	fn test_fast_solve_line () {
+
+		let cases: Vec <(& str, CluesLine, Option <& str>)> = vec! [
+			("----------", vec! [ 3, 2, 3 ], Some ("### ## ###")),
+			("----------", vec! [ 3, 4 ], Some ("--#---##--")),
+			("----- ----", vec! [ 3, 4 ], Some ("--#-- ####")),
+			("----# ----", vec! [ 3, 4 ], Some ("  ### ####")),
+			("-#---#----", vec! [ 3, 4 ], Some ("-##--###- ")),
+			("--- #-----", vec! [ 2, 3 ], Some ("--- ##----")),
+			("-#- -#-", vec! [ 3 ], None),
+			("--#--", vec! [ ], None),
+			("-----", vec! [ ], Some ("     ")),
+			("", vec! [ ], Some ("")),
+		];
+
+		for (line, clues_line, expected) in cases {
+
+			assert_eq! (
+				fast_solve_line (line, & clues_line),
+				expected.map (|expected| LineBuf::from_str (expected).unwrap ()),
+				"{:?} {:?}",
+				line,
+				clues_line,
+			);
+
+		}
+
+	}
+
+	#[ test ]
+	fn test_fast_solve_line_reuse () {
+
+		let mut line_solver = FastLineSolver::new ();
+
+		assert_eq! (
+			line_solver.solve_line (
+				& LineBuf::from_str ("-#- -#-").unwrap (),
+				& vec! [ 3 ],
+			),
+			None,
+		);
+
+		assert_eq! (
+			line_solver.solve_line (
+				& LineBuf::from_str ("-----").unwrap (),
+				& vec! [ 4 ],
+			),
+			Some (LineBuf::from_str ("-###-").unwrap ().deref ()),
+		);
+
+	}
+
+	#[ test ]
+	fn test_fast_line_solver_grid () {
+
+		let clues = load_sample ("wikipedia");
+
+		let grid = unknown_grid (& clues);
+
+		let mut grid_solver = GridSolver::new (grid.clone (), clues.clone ()).unwrap ();
+		finish (& mut grid_solver);
+
+		let mut fast_grid_solver = GridSolver::with_line_solver (
+			grid,
+			clues,
+			Default::default (),
+			FastLineSolver::new (),
+		).unwrap ();
+		finish (& mut fast_grid_solver);
+
+		assert! (fast_grid_solver.is_solved ());
+		assert_eq! (fast_grid_solver.grid (), grid_solver.grid ());
+
+	}
+
+}
@@ -0,0 +1,373 @@
+use std::fmt;
+
+use crate::data::*;
+use crate::solver::*;
+
+#[ derive (Clone, Debug, Eq, PartialEq) ]
+pub struct Hint {
+	pub line_ref: LineRef,
+	pub rule: Option <Rule>,
+	pub clue: Option <usize>,
+	pub cells: Vec <(LineSize, LineSize, Cell)>,
+	pub explanation: String,
+}
+
+impl fmt::Display for Hint {
+
+	fn fmt (& self, formatter: & mut fmt::Formatter <'_>) -> fmt::Result {
+		formatter.write_str (& self.explanation)
+	}
+
+}
+
+// finds the simplest deduction which can be made from a single line of the
+// grid, trying each named rule across every line before moving on to the next
+// one, and only falling back to full line solving when none of them apply. the
+// rules only know about filled and empty cells, so coloured clues only get
+// hints from line solving
+
+pub fn hint (
+	clues: & Clues,
+	grid: & Grid,
+) -> Option <Hint> {
+
+	let line_refs: Vec <LineRef> = (0 .. grid.num_rows ()).map (LineRef::Row).chain (
+		(0 .. grid.num_cols ()).map (LineRef::Col),
+	).collect ();
+
+	// don't hint from a grid which already contradicts the clues
+
+	let mut line_solver = ColourLineSolver::new ();
+
+	for & line_ref in line_refs.iter () {
+		let (line, clues_line, colours) = hint_line (clues, grid, line_ref);
+		line_solver.solve_colour_line (& line, clues_line, colours) ?;
+	}
+
+	let mut rule_engine = RuleEngine::new ();
+
+	let rules: & [Rule] = if clues.is_coloured () { & [] } else { & Rule::ALL };
+
+	for & rule in rules {
+		for & line_ref in line_refs.iter () {
+
+			let (line, clues_line, _) = hint_line (clues, grid, line_ref);
+
+			let deductions = rule_engine.apply_rule (rule, & line, clues_line) ?;
+
+			let first = match deductions.first () {
+				Some (val) => val,
+				None => continue,
+			};
+
+			let cells: Vec <LineSize> = deductions.iter ().filter (
+				|deduction| deduction.clue == first.clue,
+			).map (
+				|deduction| deduction.index,
+			).collect ();
+
+			return Some (Hint {
+				line_ref: line_ref,
+				rule: Some (rule),
+				clue: first.clue,
+				cells: grid_cells (line_ref, & cells, first.cell),
+				explanation: explain (line_ref, rule, first.clue.map (
+					|clue_index| clues_line [clue_index],
+				), & cells),
+			});
+
+		}
+	}
+
+	for & line_ref in line_refs.iter () {
+
+		let (line, clues_line, colours) = hint_line (clues, grid, line_ref);
+
+		let solved_line = line_solver.solve_colour_line (& line, clues_line, colours) ?;
+
+		let cells: Vec <(LineSize, LineSize, Cell)> = line.iter ().zip (
+			solved_line.iter (),
+		).enumerate ().filter (
+			|& (_, (cell, solved_cell))| cell != solved_cell,
+		).flat_map (
+			|(index, (_, solved_cell))| grid_cells (line_ref, & [ index as LineSize ], solved_cell),
+		).collect ();
+
+		if cells.is_empty () {
+			continue;
+		}
+
+		let indexes: Vec <LineSize> = cells.iter ().map (
+			|& (row_index, col_index, _)| match line_ref {
+				LineRef::Row (_) => col_index,
+				LineRef::Col (_) => row_index,
+			},
+		).collect ();
+
+		return Some (Hint {
+			line_ref: line_ref,
+			rule: None,
+			clue: None,
+			cells: cells,
+			explanation: format! (
+				"{}, cells {} are the same in every arrangement of the clues {:?}",
+				line_ref,
+				format_cells (& indexes),
+				clues_line,
+			),
+		});
+
+	}
+
+	None
+
+}
+
+fn hint_line <'a> (
+	clues: & 'a Clues,
+	grid: & Grid,
+	line_ref: LineRef,
+) -> (LineBuf, & 'a CluesLine, & 'a [u8]) {
+
+	match line_ref {
+		LineRef::Row (index) => (grid.row (index).collect (), clues.row (index), clues.row_colours (index)),
+		LineRef::Col (index) => (grid.col (index).collect (), clues.col (index), clues.col_colours (index)),
+	}
+
+}
+
+fn grid_cells (
+	line_ref: LineRef,
+	indexes: & [LineSize],
+	cell: Cell,
+) -> Vec <(LineSize, LineSize, Cell)> {
+
+	indexes.iter ().map (
+		|& index| match line_ref {
+			LineRef::Row (row_index) => (row_index, index, cell),
+			LineRef::Col (col_index) => (index, col_index, cell),
+		},
+	).collect ()
+
+}
+
+fn explain (
+	line_ref: LineRef,
+	rule: Rule,
+	clue: Option <LineSize>,
+	cells: & [LineSize],
+) -> String {
+
+	let num_cells = cells.len ();
+	let cells = format_cells (cells);
+
+	match (rule, clue) {
+
+		(Rule::SimpleBoxes, Some (clue)) => format! (
+			"{}, clue {} must cover cells {} however the clues are placed",
+			line_ref,
+			clue,
+			cells,
+		),
+
+		(Rule::Mercury, Some (clue)) => format! (
+			"{}, clue {} must cover cells {}, as the known cells push it from both sides",
+			line_ref,
+			clue,
+			cells,
+		),
+
+		(Rule::Joining, Some (clue)) => format! (
+			"{}, cells {} join two boxes which can only both be clue {}",
+			line_ref,
+			cells,
+			clue,
+		),
+
+		(Rule::SimpleSpaces, _) => format! (
+			"{}, cells {} can't be reached by any clue, so they are spaces",
+			line_ref,
+			cells,
+		),
+
+		(Rule::Forcing, _) => format! (
+			"{}, cells {} are a gap too small for any clue which could be there",
+			line_ref,
+			cells,
+		),
+
+		(Rule::Glue, _) => format! (
+			"{}, a box next to a space or the edge must extend over cells {}",
+			line_ref,
+			cells,
+		),
+
+		(Rule::Splitting, _) => format! (
+			"{}, filling {} {} would join two boxes into a block bigger than any clue",
+			line_ref,
+			if num_cells == 1 { "cell" } else { "cells" },
+			cells,
+		),
+
+		(Rule::Punctuating, _) => format! (
+			"{}, cells {} are spaces next to a complete block",
+			line_ref,
+			cells,
+		),
+
+		(rule, _) => format! (
+			"{}, cells {} follow from {}",
+			line_ref,
+			cells,
+			rule,
+		),
+
+	}
+
+}
+
+// formats sorted cell indexes as ranges, for example "1, 3–4"
+
+fn format_cells (indexes: & [LineSize]) -> String {
+
+	let mut ranges: Vec <(LineSize, LineSize)> = Vec::new ();
+
+	for & index in indexes {
+		match ranges.last_mut () {
+			Some ((_, end)) if * end + 1 == index => * end = index,
+			_ => ranges.push ((index, index)),
+		}
+	}
+
+	ranges.iter ().map (
+		|& (start, end)| if start == end {
+			format! ("{}", start)
+		} else {
+			format! ("{}–{}", start, end)
+		},
+	).collect::<Vec <String>> ().join (", ")
+
+}
+
+#[ cfg (test) ]
+mod tests {
+
+	use super::*;
+
+	#[ test ]
+	fn test_hint_simple_boxes () {
+
+		let clues = load_clues ("rows\n4\n1 1\n1 1\n1 1\n1 1\ncols\n5\n1\n1\n1\n4\n");
+		let grid = Grid::new (5, 5);
+
+		let hint = hint (& clues, & grid).unwrap ();
+
+		assert_eq! (hint.line_ref, LineRef::Row (0));
+		assert_eq! (hint.rule, Some (Rule::SimpleBoxes));
+		assert_eq! (hint.cells, vec! [
+			(0, 1, Cell::FILLED),
+			(0, 2, Cell::FILLED),
+			(0, 3, Cell::FILLED),
+		]);
+		assert_eq! (
+			hint.to_string (),
+			"row 0, clue 4 must cover cells 1–3 however the clues are placed",
+		);
+
+	}
+
+	#[ test ]
+	fn test_hint_contradiction () {
+
+		let clues = load_clues ("rows\n2\ncols\n1\n1\n1\n");
+		let mut grid = Grid::new (1, 3);
+		grid [(0, 1)] = Cell::EMPTY;
+
+		assert_eq! (hint (& clues, & grid), None);
+
+	}
+
+	#[ test ]
+	fn test_format_cells () {
+		assert_eq! (format_cells (& [ 1, 3, 4, 5, 7 ]), "1, 3–5, 7");
+		assert_eq! (format_cells (& [ ]), "");
+	}
+
+	#[ test ]
+	fn test_explain_splitting () {
+
+		assert_eq! (
+			explain (LineRef::Row (2), Rule::Splitting, None, & [ 4 ]),
+			"row 2, filling cell 4 would join two boxes into a block bigger than any clue",
+		);
+
+		assert_eq! (
+			explain (LineRef::Row (2), Rule::Splitting, None, & [ 4, 6 ]),
+			"row 2, filling cells 4, 6 would join two boxes into a block bigger than any clue",
+		);
+
+	}
+
+	// following the hints must solve the puzzle, and every hinted cell must
+	// agree with the solution
+
+	#[ test ]
+	fn test_hint_samples () {
+
+		for name in [ "camel", "heart", "wikipedia" ] {
+
+			let clues = load_sample (name);
+
+			let (_, solutions) = count_solutions (& clues, 1);
+			let solution = & solutions [0];
+
+			let mut grid = unknown_grid (& clues);
+
+			while let Some (hint) = hint (& clues, & grid) {
+
+				assert! (! hint.cells.is_empty (), "{}", name);
+
+				for (row_index, col_index, cell) in hint.cells {
+					assert_eq! (solution [(row_index, col_index)], cell, "{} {}", name, hint.explanation);
+					assert! (grid [(row_index, col_index)].is_unknown ());
+					grid [(row_index, col_index)] = cell;
+				}
+
+			}
+
+			assert_eq! (& grid, solution, "{}", name);
+
+		}
+
+	}
+
+	// coloured hints come from line solving, and may only narrow cells down to
+	// some of the colours
+
+	#[ test ]
+	fn test_hint_colours () {
+
+		let clues = load_sample ("colours");
+
+		let (_, solutions) = count_solutions (& clues, 1);
+		let solution = & solutions [0];
+
+		let mut grid = unknown_grid (& clues);
+
+		while let Some (hint) = hint (& clues, & grid) {
+
+			assert_eq! (hint.rule, None);
+
+			for (row_index, col_index, cell) in hint.cells {
+				assert_eq! (cell.union (solution [(row_index, col_index)]), cell, "{}", hint.explanation);
+				assert_ne! (cell, grid [(row_index, col_index)]);
+				grid [(row_index, col_index)] = cell;
+			}
+
+		}
+
+		assert_eq! (& grid, solution);
+
+	}
+
+}
@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::mem;
+
+use crate::data::*;
+use crate::misc::*;
+use crate::solver::*;
+
+// wraps another line solver, remembering the results for recently seen
+// combinations of clues and line, up to a maximum number of entries
+
+#[ derive (Default) ]
+pub struct CachedLineSolver <Solver: LineSolve> {
+	inner: Solver,
+	capacity: usize,
+	entries: HashMap <(CluesLine, LineBuf), Option <LineBuf>>,
+	order: VecDeque <(CluesLine, LineBuf)>,
+	key: (CluesLine, LineBuf),
+	line: LineBuf,
+	hits: usize,
+	misses: usize,
+}
+
+impl <Solver: LineSolve> CachedLineSolver <Solver> {
+
+	pub fn new (
+		inner: Solver,
+		capacity: usize,
+	) -> CachedLineSolver <Solver> {
+
+		CachedLineSolver {
+			inner: inner,
+			capacity: capacity,
+			entries: HashMap::new (),
+			order: VecDeque::new (),
+			key: Default::default (),
+			line: Default::default (),
+			hits: 0,
+			misses: 0,
+		}
+
+	}
+
+	pub fn inner (& self) -> & Solver {
+		& self.inner
+	}
+
+	pub fn hits (& self) -> usize {
+		self.hits
+	}
+
+	pub fn misses (& self) -> usize {
+		self.misses
+	}
+
+	pub fn len (& self) -> usize {
+		self.entries.len ()
+	}
+
+	pub fn is_empty (& self) -> bool {
+		self.entries.is_empty ()
+	}
+
+	// looks up a line without solving it, for callers which solve the misses
+	// elsewhere and hand the results back with store
+
+	pub fn lookup <
+		LineIter: IntoIterator <Item = Cell>,
+	> (
+		& mut self,
+		line_iter: LineIter,
+		clues_line: & CluesLine,
+	) -> Option <Option <LineBuf>> {
+
+		if self.capacity == 0 {
+			return None;
+		}
+
+		self.set_key (line_iter, clues_line);
+
+		match self.entries.get (& self.key) {
+			Some (result) => {
+				self.hits += 1;
+				Some (result.clone ())
+			},
+			None => {
+				self.misses += 1;
+				None
+			},
+		}
+
+	}
+
+	pub fn store <
+		LineIter: IntoIterator <Item = Cell>,
+	> (
+		& mut self,
+		line_iter: LineIter,
+		clues_line: & CluesLine,
+		result: Option <LineBuf>,
+	) {
+
+		if self.capacity == 0 {
+			return;
+		}
+
+		self.set_key (line_iter, clues_line);
+
+		if ! self.entries.contains_key (& self.key) {
+			self.insert (result);
+		}
+
+	}
+
+	fn set_key <
+		LineIter: IntoIterator <Item = Cell>,
+	> (
+		& mut self,
+		line_iter: LineIter,
+		clues_line: & CluesLine,
+	) {
+
+		let (key_clues, key_line) = mem::take (& mut self.key);
+
+		self.key = (
+			key_clues.into_default ().into_extend (clues_line.iter ().cloned ()),
+			key_line.into_copy_of (line_iter),
+		);
+
+	}
+
+	fn insert (& mut self, result: Option <LineBuf>) {
+
+		if self.entries.len () == self.capacity {
+			if let Some (oldest) = self.order.pop_front () {
+				self.entries.remove (& oldest);
+			}
+		}
+
+		self.entries.insert (self.key.clone (), result);
+		self.order.push_back (self.key.clone ());
+
+	}
+
+}
+
+impl <Solver: LineSolve> LineSolve for CachedLineSolver <Solver> {
+
+	fn solve_line <
+		LineIter: IntoIterator <Item = Cell>,
+	> (
+		& mut self,
+		line_iter: LineIter,
+		clues_line: & CluesLine,
+	) -> Option <& Line> {
+
+		if self.capacity == 0 {
+			return self.inner.solve_line (line_iter, clues_line);
+		}
+
+		self.set_key (line_iter, clues_line);
+
+		if let Some (result) = self.entries.get (& self.key) {
+
+			self.hits += 1;
+
+			let line = mem::take (& mut self.line);
+			self.line = line.into_copy_of (result.as_ref () ?);
+
+			return Some (& self.line);
+
+		}
+
+		self.misses += 1;
+
+		let result = self.inner.solve_line (
+			& self.key.1,
+			clues_line,
+		).map (ToOwned::to_owned);
+
+		self.insert (result.clone ());
+
+		let line = mem::take (& mut self.line);
+		self.line = line.into_copy_of (& result ?);
+
+		Some (& self.line)
+
+	}
+
+}
+
+#[ cfg (test) ]
+mod tests {
+
+	use std::ops::Deref;
+	use std::str::FromStr;
+
+	use super::*;
+
+	#[ test ]
+	fn test_cached_line_solver () {
+
+		let mut line_solver = CachedLineSolver::new (LineSolver::default (), 2);
+
+		let line_1 = LineBuf::from_str ("----------").unwrap ();
+		let line_2 = LineBuf::from_str ("----# ----").unwrap ();
+		let line_3 = LineBuf::from_str ("-#- -#-").unwrap ();
+
+		let solved_1 = LineBuf::from_str ("### ## ###").unwrap ();
+		let solved_2 = LineBuf::from_str ("  ### ####").unwrap ();
+
+		assert_eq! (line_solver.solve_line (& line_1, & vec! [ 3, 2, 3 ]), Some (solved_1.deref ()));
+		assert_eq! (line_solver.solve_line (& line_1, & vec! [ 3, 2, 3 ]), Some (solved_1.deref ()));
+		assert_eq! ((line_solver.hits (), line_solver.misses ()), (1, 1));
+
+		assert_eq! (line_solver.solve_line (& line_2, & vec! [ 3, 4 ]), Some (solved_2.deref ()));
+		assert_eq! (line_solver.solve_line (& line_3, & vec! [ 3 ]), None);
+		assert_eq! (line_solver.solve_line (& line_3, & vec! [ 3 ]), None);
+		assert_eq! ((line_solver.hits (), line_solver.misses ()), (2, 3));
+		assert_eq! (line_solver.len (), 2);
+
+		// the oldest entry was evicted
+
+		assert_eq! (line_solver.solve_line (& line_1, & vec! [ 3, 2, 3 ]), Some (solved_1.deref ()));
+		assert_eq! ((line_solver.hits (), line_solver.misses ()), (2, 4));
+
+	}
+
+	#[ test ]
+	fn test_cached_line_solver_lookup () {
+
+		let mut line_solver = CachedLineSolver::new (LineSolver::default (), 2);
+
+		let line = LineBuf::from_str ("----------").unwrap ();
+		let solved = LineBuf::from_str ("### ## ###").unwrap ();
+
+		assert_eq! (line_solver.lookup (& line, & vec! [ 3, 2, 3 ]), None);
+		line_solver.store (& line, & vec! [ 3, 2, 3 ], Some (solved.clone ()));
+		assert_eq! (line_solver.lookup (& line, & vec! [ 3, 2, 3 ]), Some (Some (solved.clone ())));
+		assert_eq! (line_solver.solve_line (& line, & vec! [ 3, 2, 3 ]), Some (solved.deref ()));
+		assert_eq! ((line_solver.hits (), line_solver.misses ()), (2, 1));
+
+	}
+
+	#[ test ]
+	fn test_cached_line_solver_disabled () {
+
+		let mut line_solver = CachedLineSolver::new (LineSolver::default (), 0);
+
+		let line = LineBuf::from_str ("----------").unwrap ();
+
+		for _ in 0 .. 2 {
+			assert! (line_solver.solve_line (& line, & vec! [ 3, 2, 3 ]).is_some ());
+		}
+
+		assert_eq! ((line_solver.hits (), line_solver.misses ()), (0, 0));
+		assert_eq! (line_solver.len (), 0);
+
+	}
+
+}
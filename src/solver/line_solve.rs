@@ -0,0 +1,16 @@
+use crate::data::*;
+
+pub trait LineSolve {
+
+	// refines the line using the clues, returning the refined line or none if
+	// the clues can't be placed at all
+
+	fn solve_line <
+		LineIter: IntoIterator <Item = Cell>,
+	> (
+		& mut self,
+		line_iter: LineIter,
+		clues_line: & CluesLine,
+	) -> Option <& Line>;
+
+}
@@ -17,18 +17,36 @@ pub fn solve_line <
 
 }
 
-#[ derive (Default) ]
+// lines at least this long are also kept as packed lines, so clue placement
+// can check runs of cells a word at a time
+
+pub const DEFAULT_PACKED_THRESHOLD: LineSize = 16;
+
 pub struct LineSolver {
 	clues_line: CluesLine,
 	line: LineBuf,
 	sample_line: LineBuf,
 	proposed_line: LineBuf,
+	packed_line: PackedLine,
+	packed_proposed_line: PackedLine,
+	packed_threshold: LineSize,
 	index: LineSize,
 	clues_placer: CluesPlacer <'static>,
 }
 
+impl Default for LineSolver {
+
+	fn default () -> LineSolver {
+		LineSolver::with_packed_threshold (DEFAULT_PACKED_THRESHOLD)
+	}
+
+}
+
 impl LineSolver {
 
+	// both results hold a solver, so callers can carry on with either
+
+	#[ allow (clippy::result_large_err) ]
 	pub fn new <
 		LineIter: IntoIterator <Item = Cell>,
 	> (
@@ -42,6 +60,24 @@ impl LineSolver {
 
 	}
 
+	pub fn with_packed_threshold (
+		packed_threshold: LineSize,
+	) -> LineSolver {
+
+		LineSolver {
+			clues_line: Default::default (),
+			line: Default::default (),
+			sample_line: Default::default (),
+			proposed_line: Default::default (),
+			packed_line: Default::default (),
+			packed_proposed_line: Default::default (),
+			packed_threshold: packed_threshold,
+			index: 0,
+			clues_placer: Default::default (),
+		}
+
+	}
+
 	pub fn into_default (
 		self,
 	) -> LineSolver {
@@ -51,12 +87,16 @@ impl LineSolver {
 			line: self.line.into_default (),
 			sample_line: self.sample_line.into_default (),
 			proposed_line: self.proposed_line.into_default (),
+			packed_line: self.packed_line.into_default (),
+			packed_proposed_line: self.packed_proposed_line.into_default (),
+			packed_threshold: self.packed_threshold,
 			index: 0,
 			clues_placer: self.clues_placer.into_default (),
 		}
 
 	}
 
+	#[ allow (clippy::result_large_err) ]
 	pub fn into_new <
 		LineIter: IntoIterator <Item = Cell>,
 	> (
@@ -66,16 +106,43 @@ impl LineSolver {
 	) -> Result <LineSolver, LineSolver> {
 
 		let line = self.line.into_copy_of (line_iter);
+		let packed = line.len () >= self.packed_threshold;
+
+		let packed_line = if packed {
+			self.packed_line.into_copy_of (& line)
+		} else {
+			self.packed_line.into_default ()
+		};
 
 		let (clues_placer, sample_line) = {
 
-			let mut clues_placer = self.clues_placer.into_new (
-				& line,
-				& clues_line,
-			);
+			let mut clues_placer = if packed {
+				self.clues_placer.into_new_packed (
+					& line,
+					& packed_line,
+					clues_line,
+				)
+			} else {
+				self.clues_placer.into_new (
+					& line,
+					clues_line,
+				)
+			};
 
 			if ! clues_placer.advance () {
-				return Err (Default::default ()); // TODO
+
+				return Err (LineSolver {
+					clues_placer: clues_placer.into_default (),
+					clues_line: self.clues_line.into_default (),
+					line: line.into_default (),
+					sample_line: self.sample_line.into_default (),
+					proposed_line: self.proposed_line.into_default (),
+					packed_line: packed_line.into_default (),
+					packed_proposed_line: self.packed_proposed_line.into_default (),
+					packed_threshold: self.packed_threshold,
+					index: 0,
+				});
+
 			};
 
 			let sample_line = self.sample_line.into_copy_of (
@@ -92,6 +159,12 @@ impl LineSolver {
 
 		let proposed_line = self.proposed_line.into_copy_of (& line);
 
+		let packed_proposed_line = if packed {
+			self.packed_proposed_line.into_copy_of (& line)
+		} else {
+			self.packed_proposed_line.into_default ()
+		};
+
 		Ok (LineSolver {
 			clues_line: self.clues_line.into_default ().into_extend (
 				clues_line.iter ().cloned (),
@@ -99,12 +172,18 @@ impl LineSolver {
 			line: line,
 			sample_line: sample_line,
 			proposed_line: proposed_line,
+			packed_line: packed_line,
+			packed_proposed_line: packed_proposed_line,
+			packed_threshold: self.packed_threshold,
 			index: 0,
 			clues_placer: clues_placer,
 		})
 
 	}
 
+	// solves one more cell, LineSolverIter wraps this up as an iterator
+
+	#[ allow (clippy::should_implement_trait) ]
 	pub fn next (& mut self) -> Option <Cell> {
 
 		if self.index == self.line.len () {
@@ -133,22 +212,30 @@ impl LineSolver {
 
 		let sample_cell = self.sample_line [self.index];
 
-		self.proposed_line [self.index] = match sample_cell {
+		self.set_proposed_cell (match sample_cell {
 			Cell::EMPTY => Cell::FILLED,
 			Cell::FILLED => Cell::EMPTY,
 			Cell::UNKNOWN => return existing_cell,
 			_ => panic! (),
-		};
+		});
 
 		// try placing clues
 
 		let mut clues_placer = Default::default ();
 		mem::swap (& mut clues_placer, & mut self.clues_placer);
 
-		let mut clues_placer = clues_placer.into_new (
-			& self.proposed_line,
-			& self.clues_line,
-		);
+		let mut clues_placer = if self.is_packed () {
+			clues_placer.into_new_packed (
+				& self.proposed_line,
+				& self.packed_proposed_line,
+				& self.clues_line,
+			)
+		} else {
+			clues_placer.into_new (
+				& self.proposed_line,
+				& self.clues_line,
+			)
+		};
 
 		// if it fails the sample cell must be correct
 
@@ -156,7 +243,7 @@ impl LineSolver {
 
 			self.clues_placer = clues_placer.into_default ();
 
-			self.proposed_line [self.index] = sample_cell;
+			self.set_proposed_cell (sample_cell);
 			self.line [self.index] = sample_cell;
 
 			return sample_cell;
@@ -183,9 +270,54 @@ impl LineSolver {
 
 		self.clues_placer = clues_placer.into_default ();
 
-		self.proposed_line [self.index] = Cell::UNKNOWN;
+		self.set_proposed_cell (Cell::UNKNOWN);
 
-		return existing_cell;
+		existing_cell
+
+	}
+
+	fn is_packed (& self) -> bool {
+		self.line.len () >= self.packed_threshold
+	}
+
+	fn set_proposed_cell (& mut self, cell: Cell) {
+
+		self.proposed_line [self.index] = cell;
+
+		if self.is_packed () {
+			self.packed_proposed_line.set (self.index, cell);
+		}
+
+	}
+
+}
+
+impl LineSolve for LineSolver {
+
+	fn solve_line <
+		LineIter: IntoIterator <Item = Cell>,
+	> (
+		& mut self,
+		line_iter: LineIter,
+		clues_line: & CluesLine,
+	) -> Option <& Line> {
+
+		let mut line_solver = Default::default ();
+		mem::swap (& mut line_solver, self);
+
+		* self = match line_solver.into_new (line_iter, clues_line) {
+			Ok (val) => val,
+			Err (val) => {
+				* self = val;
+				return None;
+			},
+		};
+
+		// solving a cell updates the line in place
+
+		while self.next ().is_some () { }
+
+		Some (& self.line)
 
 	}
 
@@ -220,7 +352,6 @@ impl Iterator for LineSolverIter {
 
 #[ inline (always) ]
 pub fn render_placed_clues <
-	'a,
 	CluesLine: IntoIterator <Item = LineSize>,
 	PlacedClues: IntoIterator <Item = LineSize>,
 > (
@@ -230,15 +361,15 @@ pub fn render_placed_clues <
 ) -> impl Iterator <Item = Cell> {
 
 	placed_clues.into_iter ().zip (
-		clues_line.into_iter (),
+		clues_line,
 	).chain (
 		iter::once ((line_size, 0)),
 	).scan (0, move |pos: & mut LineSize, (start, size)| {
 
 		let result = iter::empty ().chain (
-			iter::repeat (Cell::EMPTY).take ((start - * pos) as usize),
+			iter::repeat_n (Cell::EMPTY, (start - * pos) as usize),
 		).chain (
-			iter::repeat (Cell::FILLED).take (size as usize),
+			iter::repeat_n (Cell::FILLED, size as usize),
 		);
 
 		* pos = start + size;
@@ -252,6 +383,9 @@ pub fn render_placed_clues <
 #[ cfg (test) ]
 mod tests {
 
+	use std::ops::Deref;
+	use std::str::FromStr;
+
 	use super::*;
 
 	#[ test ]
@@ -332,5 +466,36 @@ mod tests {
 
 	}
 
+	#[ test ]
+	fn test_line_solve () {
+
+		let mut line_solver = LineSolver::default ();
+
+		assert_eq! (
+			line_solver.solve_line (
+				& LineBuf::from_str ("----# ----").unwrap (),
+				& vec! [ 3, 4 ],
+			),
+			Some (LineBuf::from_str ("  ### ####").unwrap ().deref ()),
+		);
+
+		assert_eq! (
+			line_solver.solve_line (
+				& LineBuf::from_str ("-#- -#-").unwrap (),
+				& vec! [ 3 ],
+			),
+			None,
+		);
+
+		assert_eq! (
+			line_solver.solve_line (
+				& LineBuf::from_str ("----------").unwrap (),
+				& vec! [ 3, 2, 3 ],
+			),
+			Some (LineBuf::from_str ("### ## ###").unwrap ().deref ()),
+		);
+
+	}
+
 }
 
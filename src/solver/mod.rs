@@ -1,10 +1,46 @@
+mod brute_force_line_solver;
+mod checkpoint;
 mod clue_placer;
 mod clues_placer;
+mod colour_line_solver;
+mod fast_line_solver;
+mod hint;
+mod line_cache;
+mod line_solve;
 mod line_solver;
+mod overlap_line_solver;
+mod parallel;
+mod proof;
+mod propagate;
+mod rating;
+mod rules;
+mod solutions;
 mod solve_grid;
+mod solve_grid_error;
 
+#[ cfg (test) ]
+mod test_helpers;
+
+pub use brute_force_line_solver::*;
+pub use checkpoint::*;
 pub use clue_placer::*;
 pub use clues_placer::*;
+pub use colour_line_solver::*;
+pub use fast_line_solver::*;
+pub use hint::*;
+pub use line_cache::*;
+pub use line_solve::*;
 pub use line_solver::*;
+pub use overlap_line_solver::*;
+pub use parallel::*;
+pub use proof::*;
+pub use propagate::*;
+pub use rating::*;
+pub use rules::*;
+pub use solutions::*;
 pub use solve_grid::*;
+pub use solve_grid_error::*;
+
+#[ cfg (test) ]
+pub use test_helpers::*;
 
@@ -0,0 +1,211 @@
+use std::mem;
+
+use crate::data::*;
+use crate::solver::*;
+
+// line solver which only applies the simple overlap technique, placing every
+// clue as far left and as far right as it will go and marking cells which are
+// covered by the same clue either way as filled, and cells which no clue can
+// reach as empty
+
+#[ derive (Default) ]
+pub struct OverlapLineSolver {
+	line: LineBuf,
+}
+
+impl OverlapLineSolver {
+
+	pub fn new () -> OverlapLineSolver {
+		Default::default ()
+	}
+
+}
+
+// returns the start of each clue in the leftmost and the rightmost placements
+// which are consistent with the line, or none if the clues can't be placed
+
+pub fn extreme_placements (
+	line: & Line,
+	clues_line: & CluesLine,
+) -> Option <(Vec <LineSize>, Vec <LineSize>)> {
+
+	let left = place_clues (line, clues_line).next () ?;
+
+	// the rightmost placement is the leftmost placement of the reversed line
+
+	let reversed_line: LineBuf = line.iter ().rev ().collect ();
+	let reversed_clues_line: CluesLine = clues_line.iter ().rev ().cloned ().collect ();
+
+	let right = place_clues (
+		& reversed_line,
+		& reversed_clues_line,
+	).next ().unwrap ().into_iter ().rev ().zip (clues_line.iter ()).map (
+		|(reversed_start, & size)| line.len () - reversed_start - size,
+	).collect ();
+
+	Some ((left, right))
+
+}
+
+impl LineSolve for OverlapLineSolver {
+
+	fn solve_line <
+		LineIter: IntoIterator <Item = Cell>,
+	> (
+		& mut self,
+		line_iter: LineIter,
+		clues_line: & CluesLine,
+	) -> Option <& Line> {
+
+		let line = mem::take (& mut self.line);
+		self.line = line.into_copy_of (line_iter);
+
+		let (left, right) = extreme_placements (& self.line, clues_line) ?;
+
+		for cell_index in 0 .. self.line.len () {
+
+			if ! self.line [cell_index].is_unknown () {
+				continue;
+			}
+
+			let mut reachable = false;
+
+			for (clue_index, & size) in clues_line.iter ().enumerate () {
+
+				if right [clue_index] <= cell_index && cell_index < left [clue_index] + size {
+					self.line [cell_index] = Cell::FILLED;
+				}
+
+				if left [clue_index] <= cell_index && cell_index < right [clue_index] + size {
+					reachable = true;
+				}
+
+			}
+
+			if ! reachable {
+				self.line [cell_index] = Cell::EMPTY;
+			}
+
+		}
+
+		Some (& self.line)
+
+	}
+
+}
+
+#[ cfg (test) ]
+mod tests {
+
+	use std::str::FromStr;
+
+	use super::*;
+
+	#[ test ]
+	fn test_overlap_solve_line () {
+
+		let mut line_solver = OverlapLineSolver::new ();
+
+		let cases: Vec <(& str, CluesLine, Option <& str>)> = vec! [
+			("----------", vec! [ 3, 2, 3 ], Some ("### ## ###")),
+			("----------", vec! [ 8 ], Some ("--######--")),
+			("----------", vec! [ 3, 4 ], Some ("--#---##--")),
+			("----# ----", vec! [ 3, 4 ], Some ("  ### ####")),
+			("-#--------", vec! [ 2 ], Some ("-#-       ")),
+			("-#- -#-", vec! [ 3 ], None),
+			("-----", vec! [ ], Some ("     ")),
+		];
+
+		for (line, clues_line, expected) in cases {
+
+			assert_eq! (
+				line_solver.solve_line (
+					& LineBuf::from_str (line).unwrap (),
+					& clues_line,
+				).map (ToOwned::to_owned),
+				expected.map (|expected| LineBuf::from_str (expected).unwrap ()),
+				"{:?} {:?}",
+				line,
+				clues_line,
+			);
+
+		}
+
+	}
+
+
+	// the overlap technique may find less than full line solving, but anything
+	// it does find must agree with the reference solver
+
+	#[ test ]
+	fn test_overlap_solve_line_sound () {
+
+		let mut line_solver = OverlapLineSolver::new ();
+		let mut reference_solver = BruteForceLineSolver::new ();
+
+		for line_size in 0 .. 7 {
+
+			let clues_lines: Vec <CluesLine> = (0 .. 1 << line_size).map (
+				|solution: u32| {
+					let mut clues_line = Vec::new ();
+					let mut run = 0;
+					for cell_index in 0 .. line_size + 1 {
+						if cell_index < line_size && solution & (1 << cell_index) != 0 {
+							run += 1;
+						} else if run > 0 {
+							clues_line.push (run);
+							run = 0;
+						}
+					}
+					clues_line
+				},
+			).collect ();
+
+			for line_index in 0 .. 3_u32.pow (line_size) {
+
+				let line: LineBuf = (0 .. line_size).map (
+					|cell_index| match line_index / 3_u32.pow (cell_index) % 3 {
+						0 => Cell::UNKNOWN,
+						1 => Cell::EMPTY,
+						_ => Cell::FILLED,
+					},
+				).collect ();
+
+				for clues_line in clues_lines.iter () {
+
+					let expected = reference_solver.solve_line (& line, clues_line)
+						.map (ToOwned::to_owned);
+
+					let actual = line_solver.solve_line (& line, clues_line)
+						.map (ToOwned::to_owned);
+
+					match (actual, expected) {
+						(None, None) => (),
+						(Some (actual), Some (expected)) => assert! (
+							actual.iter ().zip (expected.iter ()).all (
+								|(actual, expected)| actual.is_unknown () || actual == expected,
+							),
+							"{:?} {:?}: {:?} {:?}",
+							line,
+							clues_line,
+							actual,
+							expected,
+						),
+						(actual, expected) => panic! (
+							"{:?} {:?}: {:?} {:?}",
+							line,
+							clues_line,
+							actual,
+							expected,
+						),
+					}
+
+				}
+
+			}
+
+		}
+
+	}
+
+}
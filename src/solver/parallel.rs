@@ -0,0 +1,93 @@
+use std::thread;
+
+use crate::data::*;
+use crate::solver::*;
+
+// solves the given lines of the grid concurrently, one chunk of lines per line
+// solver, and returns the results in the same order as the lines were given
+
+pub fn solve_lines_parallel <Solver: LineSolve + Send> (
+	grid: & Grid,
+	clues: & Clues,
+	line_refs: & [LineRef],
+	line_solvers: & mut [Solver],
+) -> Vec <(LineRef, Option <LineBuf>)> {
+
+	if line_refs.is_empty () || line_solvers.is_empty () {
+		return Vec::new ();
+	}
+
+	let chunk_size = line_refs.len ().div_ceil (line_solvers.len ());
+
+	thread::scope (|scope| {
+
+		let handles: Vec <_> = line_refs.chunks (chunk_size).zip (
+			line_solvers.iter_mut (),
+		).map (
+			|(chunk, line_solver)| scope.spawn (
+				move || chunk.iter ().map (
+					|& line_ref| (
+						line_ref,
+						solve_line_ref (grid, clues, line_ref, line_solver),
+					),
+				).collect::<Vec <_>> (),
+			),
+		).collect ();
+
+		handles.into_iter ().flat_map (
+			|handle| handle.join ().unwrap (),
+		).collect ()
+
+	})
+
+}
+
+fn solve_line_ref <Solver: LineSolve> (
+	grid: & Grid,
+	clues: & Clues,
+	line_ref: LineRef,
+	line_solver: & mut Solver,
+) -> Option <LineBuf> {
+
+	match line_ref {
+		LineRef::Row (index) =>
+			line_solver.solve_line (grid.row (index), clues.row (index)),
+		LineRef::Col (index) =>
+			line_solver.solve_line (grid.col (index), clues.col (index)),
+	}.map (ToOwned::to_owned)
+
+}
+
+#[ cfg (test) ]
+mod tests {
+
+	use std::str::FromStr;
+
+	use super::*;
+
+	#[ test ]
+	fn test_solve_lines_parallel () {
+
+		let clues = load_clues ("rows\n1 1\n3\n2\ncols\n2\n2\n3\n");
+
+		let grid = Grid::new (3, 3);
+
+		let mut line_solvers: Vec <LineSolver> = (0 .. 2).map (|_| Default::default ()).collect ();
+
+		assert_eq! (
+			solve_lines_parallel (
+				& grid,
+				& clues,
+				& [ LineRef::Row (0), LineRef::Row (2), LineRef::Col (2) ],
+				& mut line_solvers,
+			),
+			vec! [
+				(LineRef::Row (0), Some (LineBuf::from_str ("# #").unwrap ())),
+				(LineRef::Row (2), Some (LineBuf::from_str ("-#-").unwrap ())),
+				(LineRef::Col (2), Some (LineBuf::from_str ("###").unwrap ())),
+			],
+		);
+
+	}
+
+}
@@ -0,0 +1,613 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::fmt::Write as _;
+
+use crate::data::*;
+use crate::solver::*;
+
+#[ derive (Clone, Debug, Eq, PartialEq) ]
+pub enum ProofStep {
+
+	Line (LineRef, Vec <(LineSize, LineSize)>),
+
+	// probes and backtracks rely on every cell known when they were made, but
+	// a guess relies on nothing, and is an assumption
+
+	Probe (LineSize, LineSize, Vec <(LineSize, LineSize)>),
+	Guess (LineSize, LineSize),
+	Backtrack (LineSize, LineSize, Vec <(LineSize, LineSize)>),
+
+}
+
+impl fmt::Display for ProofStep {
+
+	fn fmt (& self, formatter: & mut fmt::Formatter <'_>) -> fmt::Result {
+
+		match * self {
+			ProofStep::Line (line_ref, _) =>
+				write! (formatter, "solving {}", line_ref),
+			ProofStep::Probe (row_index, col_index, _) =>
+				write! (formatter, "probing ({}, {})", row_index, col_index),
+			ProofStep::Guess (row_index, col_index) =>
+				write! (formatter, "guessing ({}, {})", row_index, col_index),
+			ProofStep::Backtrack (row_index, col_index, _) =>
+				write! (formatter, "backtracking ({}, {})", row_index, col_index),
+		}
+
+	}
+
+}
+
+impl ProofStep {
+
+	pub fn depends (& self) -> & [(LineSize, LineSize)] {
+		match * self {
+			ProofStep::Line (_, ref depends) => depends,
+			ProofStep::Probe (_, _, ref depends) => depends,
+			ProofStep::Guess (..) => & [],
+			ProofStep::Backtrack (_, _, ref depends) => depends,
+		}
+	}
+
+}
+
+// records which steps solved each cell of the grid, and which cells each step
+// relied on, so that the reasons for a cell can be followed back to the empty
+// grid. colour cells may be narrowed down by several steps before they are
+// solved, so every step is kept, oldest first.
+
+#[ derive (Clone, Debug, Default) ]
+pub struct Proof {
+	num_cols: LineSize,
+	steps: Vec <ProofStep>,
+	cells: Vec <Vec <usize>>,
+}
+
+impl Proof {
+
+	pub fn new (num_rows: LineSize, num_cols: LineSize) -> Proof {
+
+		Proof {
+			num_cols: num_cols,
+			steps: Vec::new (),
+			cells: vec! [Vec::new (); num_rows as usize * num_cols as usize],
+		}
+
+	}
+
+	pub fn add_step (& mut self, step: ProofStep) -> usize {
+		self.steps.push (step);
+		self.steps.len () - 1
+	}
+
+	pub fn set_cell (
+		& mut self,
+		row_index: LineSize,
+		col_index: LineSize,
+		step_index: usize,
+	) {
+
+		let index = self.cell_index (row_index, col_index);
+
+		if self.cells [index].last () != Some (& step_index) {
+			self.cells [index].push (step_index);
+		}
+
+	}
+
+	// forgets what every cell learned from the given step onwards, for going
+	// back to the grid as it was before a guess
+
+	pub fn rewind (& mut self, step_index: usize) {
+
+		for cell_steps in self.cells.iter_mut () {
+			cell_steps.retain (|& cell_step| cell_step < step_index);
+		}
+
+	}
+
+	pub fn last_guess (& self, row_index: LineSize, col_index: LineSize) -> Option <usize> {
+
+		self.steps.iter ().rposition (
+			|step| * step == ProofStep::Guess (row_index, col_index),
+		)
+
+	}
+
+	// the step which last changed the cell
+
+	pub fn step (& self, row_index: LineSize, col_index: LineSize) -> Option <& ProofStep> {
+		self.steps (row_index, col_index).last ()
+	}
+
+	pub fn steps (
+		& self,
+		row_index: LineSize,
+		col_index: LineSize,
+	) -> impl DoubleEndedIterator <Item = & ProofStep> {
+
+		self.cells [self.cell_index (row_index, col_index)].iter ().map (
+			|& step_index| & self.steps [step_index],
+		)
+
+	}
+
+	// the cells relied on by any of the steps which changed the cell, apart
+	// from the cell itself, as a colour cell's later steps rely on what its
+	// earlier ones found
+
+	pub fn depends (
+		& self,
+		row_index: LineSize,
+		col_index: LineSize,
+	) -> Vec <(LineSize, LineSize)> {
+
+		let mut depends = Vec::new ();
+
+		for step in self.steps (row_index, col_index) {
+			for & position in step.depends () {
+				if position != (row_index, col_index) && ! depends.contains (& position) {
+					depends.push (position);
+				}
+			}
+		}
+
+		depends
+
+	}
+
+	// every cell the given cell relies on, directly or indirectly, with each
+	// cell after the ones it relies on and the given cell last. a colour cell
+	// appears once for each of its steps which something relied on, as later
+	// steps may rely on cells which relied on the cell's earlier state.
+
+	pub fn chain (
+		& self,
+		row_index: LineSize,
+		col_index: LineSize,
+	) -> Vec <(LineSize, LineSize)> {
+
+		self.chain_steps (row_index, col_index).into_iter ().map (
+			|(position, _)| position,
+		).collect ()
+
+	}
+
+	// the chain with the step each cell was changed by, if any, where a step
+	// relies on each cell as it was after that cell's last step before it
+
+	fn chain_steps (
+		& self,
+		row_index: LineSize,
+		col_index: LineSize,
+	) -> Vec <((LineSize, LineSize), Option <usize>)> {
+
+		let mut chain = Vec::new ();
+		let mut visited = HashSet::new ();
+
+		// iterative depth first search, a cell is pushed to the chain when it is
+		// popped the second time, after all of its dependencies
+
+		let last_step = self.cells [self.cell_index (row_index, col_index)].last ().cloned ();
+
+		let mut stack = vec! [ (((row_index, col_index), last_step), false) ];
+
+		while let Some ((node, expanded)) = stack.pop () {
+
+			if expanded {
+				chain.push (node);
+				continue;
+			}
+
+			if ! visited.insert (node) {
+				continue;
+			}
+
+			stack.push ((node, true));
+
+			if let (_, Some (step_index)) = node {
+				for & position in self.steps [step_index].depends ().iter ().rev () {
+					let depend = (position, self.step_before (position, step_index));
+					if ! visited.contains (& depend) {
+						stack.push ((depend, false));
+					}
+				}
+			}
+
+		}
+
+		chain
+
+	}
+
+	fn step_before (& self, position: (LineSize, LineSize), step_index: usize) -> Option <usize> {
+
+		self.cells [self.cell_index (position.0, position.1)].iter ().rev ().find (
+			|& & cell_step| cell_step < step_index,
+		).cloned ()
+
+	}
+
+	pub fn explain (
+		& self,
+		grid: & Grid,
+		row_index: LineSize,
+		col_index: LineSize,
+	) -> String {
+
+		let mut output = String::new ();
+
+		for (position, step_index) in self.chain_steps (row_index, col_index) {
+
+			write! (
+				output,
+				"({}, {}) {} by {}",
+				position.0,
+				position.1,
+				self.node_name (grid, position, step_index),
+				match step_index {
+					Some (step_index) => self.steps [step_index].to_string (),
+					None => "the starting grid".to_string (),
+				},
+			).unwrap ();
+
+			let depends = step_index.map_or (& [] as & [_], |step_index| self.steps [step_index].depends ());
+
+			if ! depends.is_empty () {
+				write! (
+					output,
+					" using {}",
+					depends.iter ().map (
+						|(depend_row, depend_col)| format! ("({}, {})", depend_row, depend_col),
+					).collect::<Vec <String>> ().join (", "),
+				).unwrap ();
+			}
+
+			if self.is_assumed (step_index) {
+				output.push_str (", which is an assumption");
+			}
+
+			output.push ('\n');
+
+		}
+
+		output
+
+	}
+
+	pub fn to_dot (
+		& self,
+		grid: & Grid,
+		row_index: LineSize,
+		col_index: LineSize,
+	) -> String {
+
+		let chain = self.chain_steps (row_index, col_index);
+
+		let mut output = String::new ();
+
+		output.push_str ("digraph proof {\n");
+
+		for & (position, step_index) in chain.iter () {
+
+			writeln! (
+				output,
+				"\t\"{}\" [label=\"({}, {}) {}\\n{}\"{}];",
+				self.node_id (position, step_index),
+				position.0,
+				position.1,
+				self.node_name (grid, position, step_index),
+				match step_index {
+					Some (step_index) => self.steps [step_index].to_string (),
+					None => "given".to_string (),
+				},
+				if self.is_assumed (step_index) { ", style=dashed" } else { "" },
+			).unwrap ();
+
+		}
+
+		for & (position, step_index) in chain.iter () {
+
+			let step_index = match step_index {
+				Some (step_index) => step_index,
+				None => continue,
+			};
+
+			for & depend in self.steps [step_index].depends () {
+
+				writeln! (
+					output,
+					"\t\"{}\" -> \"{}\";",
+					self.node_id (depend, self.step_before (depend, step_index)),
+					self.node_id (position, Some (step_index)),
+				).unwrap ();
+
+			}
+
+		}
+
+		output.push_str ("}\n");
+
+		output
+
+	}
+
+	// cells are named by their position, with the step added for earlier
+	// states of colour cells
+
+	fn node_id (& self, position: (LineSize, LineSize), step_index: Option <usize>) -> String {
+
+		let last_step = self.cells [self.cell_index (position.0, position.1)].last ().cloned ();
+
+		match step_index {
+			Some (step_index) if Some (step_index) != last_step =>
+				format! ("{},{}@{}", position.0, position.1, step_index),
+			_ => format! ("{},{}", position.0, position.1),
+		}
+
+	}
+
+	fn node_name (
+		& self,
+		grid: & Grid,
+		position: (LineSize, LineSize),
+		step_index: Option <usize>,
+	) -> & 'static str {
+
+		let last_step = self.cells [self.cell_index (position.0, position.1)].last ().cloned ();
+
+		if step_index.is_some () && step_index != last_step {
+			"narrowed"
+		} else {
+			cell_name (grid [position])
+		}
+
+	}
+
+	// a guessed cell is only assumed to be right, rather than proved
+
+	fn is_assumed (& self, step_index: Option <usize>) -> bool {
+		matches! (step_index.map (|step_index| & self.steps [step_index]), Some (ProofStep::Guess (..)))
+	}
+
+	fn cell_index (& self, row_index: LineSize, col_index: LineSize) -> usize {
+		row_index as usize * self.num_cols as usize + col_index as usize
+	}
+
+}
+
+fn cell_name (cell: Cell) -> & 'static str {
+	match cell {
+		Cell::EMPTY => "empty",
+		Cell::FILLED => "filled",
+		Cell::ERROR => "error",
+		_ if cell.is_solved () => "coloured",
+		_ => "unknown",
+	}
+}
+
+#[ cfg (test) ]
+mod tests {
+
+	use super::*;
+
+	fn solve_with_proof (clues: Clues) -> GridSolver {
+		solve_with_config (clues, Default::default ())
+	}
+
+	fn solve_with_config (clues: Clues, config: GridSolverConfig) -> GridSolver {
+
+		let mut grid_solver = GridSolver::with_config (
+			unknown_grid (& clues),
+			clues,
+			GridSolverConfig {
+				record_proof: true,
+				.. config
+			},
+		).unwrap ();
+
+		finish (& mut grid_solver);
+
+		grid_solver
+
+	}
+
+	#[ test ]
+	fn test_proof_simple () {
+
+		let grid_solver = solve_with_proof (
+			load_clues ("rows\n3\n1\ncols\n2\n1\n1\n"),
+		);
+
+		let proof = grid_solver.proof ().unwrap ();
+
+		assert_eq! (proof.step (0, 1), Some (& ProofStep::Line (LineRef::Row (0), vec! [ ])));
+		assert_eq! (proof.chain (0, 1), vec! [ (0, 1) ]);
+
+		// the first row gives the first column a box, which the column clue
+		// then extends
+
+		assert_eq! (proof.step (1, 0), Some (& ProofStep::Line (LineRef::Col (0), vec! [ (0, 0) ])));
+		assert_eq! (proof.chain (1, 0), vec! [ (0, 0), (1, 0) ]);
+
+		assert_eq! (
+			proof.explain (grid_solver.grid (), 1, 0),
+			"(0, 0) filled by solving row 0\n(1, 0) filled by solving col 0 using (0, 0)\n",
+		);
+
+		assert_eq! (
+			proof.to_dot (grid_solver.grid (), 1, 0),
+			"digraph proof {\n\
+			\t\"0,0\" [label=\"(0, 0) filled\\nsolving row 0\"];\n\
+			\t\"1,0\" [label=\"(1, 0) filled\\nsolving col 0\"];\n\
+			\t\"0,0\" -> \"1,0\";\n\
+			}\n",
+		);
+
+	}
+
+	#[ test ]
+	fn test_proof_sample () {
+
+		let grid_solver = solve_with_proof (load_sample ("wikipedia"));
+
+		let grid = grid_solver.grid ();
+		let proof = grid_solver.proof ().unwrap ();
+
+		for row_index in 0 .. grid.num_rows () {
+			for col_index in 0 .. grid.num_cols () {
+
+				assert! (proof.step (row_index, col_index).is_some ());
+
+				let chain = proof.chain (row_index, col_index);
+
+				assert_eq! (chain.last (), Some (& (row_index, col_index)));
+
+				// every cell comes after the cells it relies on
+
+				for (position, & (chain_row, chain_col)) in chain.iter ().enumerate () {
+					for depend in proof.depends (chain_row, chain_col) {
+						assert! (chain [ .. position].contains (& depend));
+					}
+				}
+
+			}
+		}
+
+	}
+
+	fn assert_chains_ordered (grid_solver: & GridSolver) {
+
+		let grid = grid_solver.grid ();
+		let proof = grid_solver.proof ().unwrap ();
+
+		for row_index in 0 .. grid.num_rows () {
+			for col_index in 0 .. grid.num_cols () {
+				let chain = proof.chain_steps (row_index, col_index);
+				for (position, & (_, step_index)) in chain.iter ().enumerate () {
+					let step_index = match step_index {
+						Some (step_index) => step_index,
+						None => continue,
+					};
+					for & depend in proof.steps [step_index].depends () {
+						let depend_node = (depend, proof.step_before (depend, step_index));
+						assert! (chain [ .. position].contains (& depend_node));
+					}
+				}
+			}
+		}
+
+	}
+
+	// probed cells rely on the cells known before the probe
+
+	#[ test ]
+	fn test_proof_probing () {
+
+		let grid_solver = solve_with_config (
+			load_clues ("rows\n1\n3\n1 1\n1 1\n1 1\ncols\n1 2\n1 1\n1\n1\n3\n"),
+			GridSolverConfig {
+				probing: true,
+				.. Default::default ()
+			},
+		);
+
+		let grid = grid_solver.grid ();
+		let proof = grid_solver.proof ().unwrap ();
+
+		let probed: Vec <(LineSize, LineSize)> = (0 .. grid.num_rows ()).flat_map (
+			|row_index| (0 .. grid.num_cols ()).map (move |col_index| (row_index, col_index)),
+		).filter (
+			|& (row_index, col_index)| matches! (proof.step (row_index, col_index), Some (ProofStep::Probe (..))),
+		).collect ();
+
+		assert! (! probed.is_empty ());
+
+		for (row_index, col_index) in probed {
+			assert! (! proof.depends (row_index, col_index).is_empty ());
+		}
+
+		assert_chains_ordered (& grid_solver);
+
+	}
+
+	// a guess is an assumption, and relies on nothing
+
+	#[ test ]
+	fn test_proof_guess () {
+
+		let grid_solver = solve_with_config (
+			load_clues ("rows\n1\n1\ncols\n1\n1\n"),
+			GridSolverConfig {
+				backtracking: true,
+				.. Default::default ()
+			},
+		);
+
+		let proof = grid_solver.proof ().unwrap ();
+
+		assert_eq! (proof.step (0, 0), Some (& ProofStep::Guess (0, 0)));
+
+		assert_eq! (
+			proof.explain (grid_solver.grid (), 0, 0),
+			"(0, 0) filled by guessing (0, 0), which is an assumption\n",
+		);
+
+		assert_eq! (
+			proof.to_dot (grid_solver.grid (), 0, 0),
+			"digraph proof {\n\
+			\t\"0,0\" [label=\"(0, 0) filled\\nguessing (0, 0)\", style=dashed];\n\
+			}\n",
+		);
+
+		assert_chains_ordered (& grid_solver);
+
+	}
+
+	// colour cells keep every step which narrowed them down, and rely on the
+	// cells used by any of them
+
+	#[ test ]
+	fn test_proof_colours () {
+
+		let grid_solver = solve_with_proof (load_sample ("colours"));
+
+		let grid = grid_solver.grid ();
+		let proof = grid_solver.proof ().unwrap ();
+
+		let mut narrowed = 0;
+
+		for row_index in 0 .. grid.num_rows () {
+			for col_index in 0 .. grid.num_cols () {
+
+				let steps: Vec <& ProofStep> = proof.steps (row_index, col_index).collect ();
+
+				if steps.len () < 2 {
+					continue;
+				}
+
+				narrowed += 1;
+
+				let depends = proof.depends (row_index, col_index);
+
+				for step in steps {
+					for depend in step.depends () {
+						assert! (* depend == (row_index, col_index) || depends.contains (depend));
+					}
+				}
+
+				// and the explanation shows the earlier steps as well
+
+				let explanation = proof.explain (grid, row_index, col_index);
+				assert! (explanation.contains (& format! ("({}, {}) narrowed by ", row_index, col_index)));
+
+			}
+		}
+
+		assert! (narrowed > 0);
+
+		assert_chains_ordered (& grid_solver);
+
+	}
+
+}
@@ -0,0 +1,165 @@
+use crate::data::*;
+use crate::solver::*;
+
+// coloured clues are solved with the colour line solver, when one is given,
+// instead of the line solver
+
+pub fn propagate <Solver: LineSolve> (
+	grid: & mut Grid,
+	clues: & Clues,
+	changed_rows: & mut [bool],
+	changed_cols: & mut [bool],
+	line_solver: & mut Solver,
+	mut colour_line_solver: Option <& mut ColourLineSolver>,
+	stats: & mut GridSolverStats,
+) -> Result <(), LineRef> {
+
+	// solve changed rows and columns alternately until nothing changes
+
+	while changed_rows.iter ().chain (changed_cols.iter ()).any (|& changed| changed) {
+
+		for row_index in 0 .. grid.num_rows () {
+
+			if ! changed_rows [row_index as usize] {
+				continue;
+			}
+
+			changed_rows [row_index as usize] = false;
+
+			stats.line_iterations += 1;
+
+			let line = match solve_line (
+				line_solver,
+				colour_line_solver.as_deref_mut (),
+				grid.row (row_index),
+				clues.row (row_index),
+				clues.row_colours (row_index),
+			) {
+				Some (val) => val,
+				None => return Err (LineRef::Row (row_index)),
+			};
+
+			for (col_index, cell) in line.iter ().enumerate () {
+
+				let col_index = col_index as LineSize;
+
+				if cell != grid [(row_index, col_index)] {
+					grid [(row_index, col_index)] = cell;
+					changed_cols [col_index as usize] = true;
+				}
+
+			}
+
+		}
+
+		for col_index in 0 .. grid.num_cols () {
+
+			if ! changed_cols [col_index as usize] {
+				continue;
+			}
+
+			changed_cols [col_index as usize] = false;
+
+			stats.line_iterations += 1;
+
+			let line = match solve_line (
+				line_solver,
+				colour_line_solver.as_deref_mut (),
+				grid.col (col_index),
+				clues.col (col_index),
+				clues.col_colours (col_index),
+			) {
+				Some (val) => val,
+				None => return Err (LineRef::Col (col_index)),
+			};
+
+			for (row_index, cell) in line.iter ().enumerate () {
+
+				let row_index = row_index as LineSize;
+
+				if cell != grid [(row_index, col_index)] {
+					grid [(row_index, col_index)] = cell;
+					changed_rows [row_index as usize] = true;
+				}
+
+			}
+
+		}
+
+	}
+
+	Ok (())
+
+}
+
+fn solve_line <
+	'a,
+	Solver: LineSolve,
+	LineIter: IntoIterator <Item = Cell>,
+> (
+	line_solver: & 'a mut Solver,
+	colour_line_solver: Option <& 'a mut ColourLineSolver>,
+	line_iter: LineIter,
+	clues_line: & CluesLine,
+	colours: & [u8],
+) -> Option <& 'a Line> {
+
+	match colour_line_solver {
+		Some (colour_line_solver) => colour_line_solver.solve_colour_line (line_iter, clues_line, colours),
+		None => line_solver.solve_line (line_iter, clues_line),
+	}
+
+}
+
+#[ cfg (test) ]
+mod tests {
+
+	use super::*;
+
+	#[ test ]
+	fn test_propagate () {
+
+		let clues = load_clues ("rows\n1 1\n3\ncols\n2\n1\n2\n");
+
+		let mut grid = Grid::new (2, 3);
+
+		assert_eq! (
+			propagate (
+				& mut grid,
+				& clues,
+				& mut [ true, true ],
+				& mut [ true, true, true ],
+				& mut LineSolver::default (),
+				None,
+				& mut GridSolverStats::new (),
+			),
+			Ok (()),
+		);
+
+		assert! (grid.is_solved ());
+
+	}
+
+	#[ test ]
+	fn test_propagate_contradiction () {
+
+		let clues = load_clues ("rows\n2\n2\ncols\n1\n1\n");
+
+		let mut grid = Grid::new (2, 2);
+
+		assert_eq! (
+			propagate (
+				& mut grid,
+				& clues,
+				& mut [ true, true ],
+				& mut [ true, true ],
+				& mut LineSolver::default (),
+				None,
+				& mut GridSolverStats::new (),
+			),
+			Err (LineRef::Col (0)),
+		);
+
+	}
+
+}
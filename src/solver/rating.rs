@@ -0,0 +1,235 @@
+use std::fmt;
+
+use crate::data::*;
+use crate::solver::*;
+
+#[ derive (Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd) ]
+pub enum Technique {
+	Overlap,
+	LineSolving,
+	Probing,
+	Backtracking (usize),
+}
+
+impl fmt::Display for Technique {
+
+	fn fmt (& self, formatter: & mut fmt::Formatter <'_>) -> fmt::Result {
+		match * self {
+			Technique::Overlap => write! (formatter, "simple overlap"),
+			Technique::LineSolving => write! (formatter, "line solving"),
+			Technique::Probing => write! (formatter, "probing"),
+			Technique::Backtracking (depth) =>
+				write! (formatter, "backtracking (depth {})", depth),
+		}
+	}
+
+}
+
+#[ derive (Clone, Copy, Debug, Eq, PartialEq) ]
+pub struct Rating {
+	pub technique: Technique,
+	pub line_solves: usize,
+	pub probes: usize,
+	pub guesses: usize,
+	pub score: usize,
+	pub unique: bool,
+}
+
+// solves the clues with progressively stronger techniques, and rates them by
+// the weakest one which is enough, returns none if there is no solution. only
+// backtracking can find one of several solutions, so then the rating says
+// whether there are others.
+
+pub fn rate (
+	clues: & Clues,
+) -> Option <Rating> {
+
+	let overlap_config = GridSolverConfig {
+		line_cache_size: 0,
+		.. Default::default ()
+	};
+
+	// coloured clues are always fully line solved, so they skip the overlap
+
+	if ! clues.is_coloured () {
+		if let Some (stats) = rate_solve (clues, overlap_config, OverlapLineSolver::new ()) {
+			return Some (Rating::new (Technique::Overlap, & stats));
+		}
+	}
+
+	if let Some (stats) = rate_solve (clues, Default::default (), LineSolver::default ()) {
+		return Some (Rating::new (Technique::LineSolving, & stats));
+	}
+
+	let probing_config = GridSolverConfig {
+		probing: true,
+		.. Default::default ()
+	};
+
+	if let Some (stats) = rate_solve (clues, probing_config, LineSolver::default ()) {
+		return Some (Rating::new (Technique::Probing, & stats));
+	}
+
+	let backtracking_config = GridSolverConfig {
+		probing: true,
+		backtracking: true,
+		.. Default::default ()
+	};
+
+	let stats = rate_solve (clues, backtracking_config, LineSolver::default ()) ?;
+
+	let (count, _) = count_solutions (clues, 1);
+
+	Some (Rating {
+		unique: count.is_unique (),
+		.. Rating::new (Technique::Backtracking (stats.max_depth), & stats)
+	})
+
+}
+
+fn rate_solve <Solver: LineSolve + Default + Send> (
+	clues: & Clues,
+	config: GridSolverConfig,
+	line_solver: Solver,
+) -> Option <GridSolverStats> {
+
+	// the grid is made to fit the clues, so this can't fail
+
+	let mut grid_solver = GridSolver::with_line_solver (
+		Grid::with_colours (clues.num_rows (), clues.num_cols (), clues.num_colours ()),
+		clues.clone (),
+		config,
+		line_solver,
+	).unwrap ();
+
+	while grid_solver.next ().is_some () { }
+
+	if grid_solver.is_solved () {
+		Some (* grid_solver.stats ())
+	} else {
+		None
+	}
+
+}
+
+impl Rating {
+
+	fn new (
+		technique: Technique,
+		stats: & GridSolverStats,
+	) -> Rating {
+
+		// each technique costs more than any amount of the ones before it
+		// would normally need, then the work done breaks ties
+
+		let technique_score = match technique {
+			Technique::Overlap => 0,
+			Technique::LineSolving => 1000,
+			Technique::Probing => 10000,
+			Technique::Backtracking (depth) => 100000 * (depth + 1),
+		};
+
+		Rating {
+			technique: technique,
+			line_solves: stats.line_iterations,
+			probes: stats.probes,
+			guesses: stats.guesses,
+			score: technique_score
+				+ stats.line_iterations
+				+ stats.probes * 10
+				+ stats.guesses * 100,
+			unique: true,
+		}
+
+	}
+
+}
+
+#[ cfg (test) ]
+mod tests {
+
+	use super::*;
+
+	#[ test ]
+	fn test_rate_overlap () {
+
+		let rating = rate (& load_clues ("rows\n3\n1\n3\ncols\n3\n1 1\n1 1\n")).unwrap ();
+
+		assert_eq! (rating.technique, Technique::Overlap);
+		assert! (rating.score < 1000);
+
+	}
+
+	#[ test ]
+	fn test_rate_samples () {
+
+		for (name, technique) in [
+			("camel", Technique::Overlap),
+			("eagle", Technique::Overlap),
+			("heart", Technique::Overlap),
+			("monkey", Technique::LineSolving),
+			("wikipedia", Technique::Overlap),
+		] {
+
+			let clues = load_sample (name);
+
+			let rating = rate (& clues).unwrap ();
+
+			assert_eq! (rating.technique, technique, "{}", name);
+			assert! (rating.unique, "{}", name);
+			assert! (rating.line_solves > 0, "{}", name);
+
+		}
+
+	}
+
+	#[ test ]
+	fn test_rate_colours () {
+
+		let clues = load_sample ("colours");
+
+		let rating = rate (& clues).unwrap ();
+
+		assert_eq! (rating.technique, Technique::LineSolving);
+		assert! (rating.line_solves > 0);
+
+		let rating = rate (& load_clues (
+			"colours\nr ff0000\nb 0000ff\nrows\n1r\n2b\n1b 1b\ncols\n1b\n1b\n1b\n1r 1b\n",
+		)).unwrap ();
+
+		assert_eq! (rating.technique, Technique::Probing);
+
+	}
+
+	#[ test ]
+	fn test_rate_probing () {
+
+		let rating = rate (& load_clues (
+			"rows\n1\n3\n1 1\n1 1\n1 1\ncols\n1 2\n1 1\n1\n1\n3\n",
+		)).unwrap ();
+
+		assert_eq! (rating.technique, Technique::Probing);
+		assert! (rating.probes > 0);
+
+	}
+
+	#[ test ]
+	fn test_rate_backtracking () {
+
+		let rating = rate (& load_clues ("rows\n1\n1\ncols\n1\n1\n")).unwrap ();
+
+		assert_eq! (rating.technique, Technique::Backtracking (1));
+		assert_eq! (rating.guesses, 1);
+
+		// either diagonal fits, so the solution found isn't the only one
+
+		assert! (! rating.unique);
+
+	}
+
+	#[ test ]
+	fn test_rate_contradiction () {
+		assert_eq! (rate (& load_clues ("rows\n2\ncols\n1\n1\n1\n")), None);
+	}
+
+}
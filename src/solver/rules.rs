@@ -0,0 +1,678 @@
+use std::fmt;
+use std::mem;
+
+use crate::data::*;
+use crate::solver::*;
+
+#[ derive (Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd) ]
+pub enum Rule {
+	SimpleBoxes,
+	SimpleSpaces,
+	Forcing,
+	Glue,
+	Joining,
+	Splitting,
+	Mercury,
+	Punctuating,
+}
+
+impl Rule {
+
+	pub const ALL: [Rule; 8] = [
+		Rule::SimpleBoxes,
+		Rule::SimpleSpaces,
+		Rule::Forcing,
+		Rule::Glue,
+		Rule::Joining,
+		Rule::Splitting,
+		Rule::Mercury,
+		Rule::Punctuating,
+	];
+
+}
+
+impl fmt::Display for Rule {
+
+	fn fmt (& self, formatter: & mut fmt::Formatter <'_>) -> fmt::Result {
+		formatter.write_str (match * self {
+			Rule::SimpleBoxes => "simple boxes",
+			Rule::SimpleSpaces => "simple spaces",
+			Rule::Forcing => "forcing",
+			Rule::Glue => "glue",
+			Rule::Joining => "joining",
+			Rule::Splitting => "splitting",
+			Rule::Mercury => "edge mercury",
+			Rule::Punctuating => "punctuating",
+		})
+	}
+
+}
+
+#[ derive (Clone, Copy, Debug, Eq, PartialEq) ]
+pub struct Deduction {
+	pub index: LineSize,
+	pub cell: Cell,
+	pub rule: Rule,
+	pub clue: Option <usize>,
+}
+
+// applies named human techniques to a line, simplest first, starting again from
+// the simplest after each one which makes progress, and records which rule
+// deduced each cell
+
+#[ derive (Default) ]
+pub struct RuleEngine {
+	line: LineBuf,
+	deductions: Vec <Deduction>,
+	left: Vec <LineSize>,
+	right: Vec <LineSize>,
+}
+
+impl RuleEngine {
+
+	pub fn new () -> RuleEngine {
+		Default::default ()
+	}
+
+	pub fn line (& self) -> & Line {
+		& self.line
+	}
+
+	pub fn deductions (& self) -> & [Deduction] {
+		& self.deductions
+	}
+
+	pub fn apply_rules <
+		LineIter: IntoIterator <Item = Cell>,
+	> (
+		& mut self,
+		line_iter: LineIter,
+		clues_line: & CluesLine,
+	) -> Option <& [Deduction]> {
+
+		let line = mem::take (& mut self.line);
+		self.line = line.into_copy_of (line_iter);
+		self.deductions.clear ();
+
+		'outer: loop {
+
+			for rule in Rule::ALL {
+				if self.try_rule (rule, clues_line) ? {
+					continue 'outer;
+				}
+			}
+
+			return Some (& self.deductions);
+
+		}
+
+	}
+
+	// applies a single rule once, for showing one step at a time
+
+	pub fn apply_rule <
+		LineIter: IntoIterator <Item = Cell>,
+	> (
+		& mut self,
+		rule: Rule,
+		line_iter: LineIter,
+		clues_line: & CluesLine,
+	) -> Option <& [Deduction]> {
+
+		let line = mem::take (& mut self.line);
+		self.line = line.into_copy_of (line_iter);
+		self.deductions.clear ();
+
+		self.try_rule (rule, clues_line) ?;
+
+		Some (& self.deductions)
+
+	}
+
+	fn try_rule (
+		& mut self,
+		rule: Rule,
+		clues_line: & CluesLine,
+	) -> Option <bool> {
+
+		let num_deductions = self.deductions.len ();
+
+		if rule != Rule::SimpleBoxes {
+			(self.left, self.right) = extreme_placements (& self.line, clues_line) ?;
+		}
+
+		match rule {
+			Rule::SimpleBoxes => self.simple_boxes (clues_line) ?,
+			Rule::SimpleSpaces => self.simple_spaces (clues_line) ?,
+			Rule::Forcing => self.forcing (clues_line) ?,
+			Rule::Glue => self.glue (clues_line) ?,
+			Rule::Joining => self.joining (clues_line) ?,
+			Rule::Splitting => self.splitting (clues_line) ?,
+			Rule::Mercury => self.mercury (clues_line) ?,
+			Rule::Punctuating => self.punctuating (clues_line) ?,
+		}
+
+		Some (self.deductions.len () > num_deductions)
+
+	}
+
+	// boxes covered by a clue however the clues are arranged, ignoring what is
+	// already known about the line
+
+	fn simple_boxes (& mut self, clues_line: & CluesLine) -> Option <()> {
+
+		let total = clues_line.iter ().sum::<LineSize> ()
+			+ (clues_line.len () as LineSize).saturating_sub (1);
+
+		if total > self.line.len () {
+			return None;
+		}
+
+		let slack = self.line.len () - total;
+		let mut start = 0;
+
+		for (clue_index, & size) in clues_line.iter ().enumerate () {
+
+			for cell_index in start + slack .. start + size {
+				self.deduce (cell_index, Cell::FILLED, Rule::SimpleBoxes, Some (clue_index)) ?;
+			}
+
+			start += size + 1;
+
+		}
+
+		Some (())
+
+	}
+
+	// spaces which no clue can reach
+
+	fn simple_spaces (& mut self, clues_line: & CluesLine) -> Option <()> {
+
+		for cell_index in 0 .. self.line.len () {
+			if self.reaching (clues_line, cell_index).next ().is_none () {
+				self.deduce (cell_index, Cell::EMPTY, Rule::SimpleSpaces, None) ?;
+			}
+		}
+
+		Some (())
+
+	}
+
+	// gaps between spaces which are too small for any clue which could be in them
+
+	fn forcing (& mut self, clues_line: & CluesLine) -> Option <()> {
+
+		for (start, end) in self.gaps () {
+
+			if (start .. end).any (|cell_index| self.line [cell_index].is_filled ()) {
+				continue;
+			}
+
+			let fits = clues_line.iter ().enumerate ().any (
+				|(clue_index, & size)|
+					self.left [clue_index] < end
+						&& start < self.right [clue_index] + size
+						&& size <= end - start,
+			);
+
+			if ! fits {
+				for cell_index in start .. end {
+					self.deduce (cell_index, Cell::EMPTY, Rule::Forcing, None) ?;
+				}
+			}
+
+		}
+
+		Some (())
+
+	}
+
+	// a box next to a space or the edge belongs to a clue which starts there, so
+	// it extends at least as far as the smallest clue it could be
+
+	fn glue (& mut self, clues_line: & CluesLine) -> Option <()> {
+
+		for (start, end) in self.runs () {
+
+			if start == 0 || self.line [start - 1].is_empty () {
+
+				let size = self.reaching (clues_line, start).map (
+					|clue_index| clues_line [clue_index],
+				).min () ?;
+
+				if start + size > self.line.len () {
+					return None;
+				}
+
+				for cell_index in start .. start + size {
+					self.deduce (cell_index, Cell::FILLED, Rule::Glue, None) ?;
+				}
+
+			}
+
+			if end == self.line.len () || self.line [end].is_empty () {
+
+				let size = self.reaching (clues_line, end - 1).map (
+					|clue_index| clues_line [clue_index],
+				).min () ?;
+
+				if size > end {
+					return None;
+				}
+
+				for cell_index in end - size .. end {
+					self.deduce (cell_index, Cell::FILLED, Rule::Glue, None) ?;
+				}
+
+			}
+
+		}
+
+		Some (())
+
+	}
+
+	// two boxes which can only belong to the same clue are joined up
+
+	fn joining (& mut self, clues_line: & CluesLine) -> Option <()> {
+
+		let runs = self.runs ();
+
+		for pair in runs.windows (2) {
+
+			let (first_start, first_end) = pair [0];
+			let (second_start, second_end) = pair [1];
+
+			let first_clue = self.only_reaching (clues_line, first_start);
+			let second_clue = self.only_reaching (clues_line, second_end - 1);
+
+			if first_clue.is_none () || first_clue != second_clue {
+				continue;
+			}
+
+			for cell_index in first_end .. second_start {
+				self.deduce (cell_index, Cell::FILLED, Rule::Joining, first_clue) ?;
+			}
+
+		}
+
+		Some (())
+
+	}
+
+	// an unknown cell between two boxes which would make a block bigger than any
+	// clue which could be there must be a space
+
+	fn splitting (& mut self, clues_line: & CluesLine) -> Option <()> {
+
+		let runs = self.runs ();
+
+		for pair in runs.windows (2) {
+
+			let (first_start, first_end) = pair [0];
+			let (second_start, second_end) = pair [1];
+
+			if second_start != first_end + 1 {
+				continue;
+			}
+
+			let max_size = self.reaching (clues_line, first_end).map (
+				|clue_index| clues_line [clue_index],
+			).max ().unwrap_or (0);
+
+			if second_end - first_start > max_size {
+				self.deduce (first_end, Cell::EMPTY, Rule::Splitting, None) ?;
+			}
+
+		}
+
+		Some (())
+
+	}
+
+	// boxes and spaces push the clues away from the edges, so the clues overlap
+	// more than they do in an empty line
+
+	fn mercury (& mut self, clues_line: & CluesLine) -> Option <()> {
+
+		for (clue_index, & size) in clues_line.iter ().enumerate () {
+			for cell_index in self.right [clue_index] .. self.left [clue_index] + size {
+				self.deduce (cell_index, Cell::FILLED, Rule::Mercury, Some (clue_index)) ?;
+			}
+		}
+
+		Some (())
+
+	}
+
+	// a block as big as the biggest clue it could be is complete, so it has
+	// spaces either side
+
+	fn punctuating (& mut self, clues_line: & CluesLine) -> Option <()> {
+
+		for (start, end) in self.runs () {
+
+			let max_size = self.reaching (clues_line, start).map (
+				|clue_index| clues_line [clue_index],
+			).max () ?;
+
+			if end - start < max_size {
+				continue;
+			}
+
+			if start > 0 {
+				self.deduce (start - 1, Cell::EMPTY, Rule::Punctuating, None) ?;
+			}
+
+			if end < self.line.len () {
+				self.deduce (end, Cell::EMPTY, Rule::Punctuating, None) ?;
+			}
+
+		}
+
+		Some (())
+
+	}
+
+	fn deduce (
+		& mut self,
+		index: LineSize,
+		cell: Cell,
+		rule: Rule,
+		clue: Option <usize>,
+	) -> Option <()> {
+
+		let existing_cell = self.line [index];
+
+		if existing_cell == cell {
+			return Some (());
+		}
+
+		if ! existing_cell.is_unknown () {
+			return None;
+		}
+
+		self.line [index] = cell;
+
+		self.deductions.push (Deduction {
+			index: index,
+			cell: cell,
+			rule: rule,
+			clue: clue,
+		});
+
+		Some (())
+
+	}
+
+	// clues which can cover the cell in some placement between the extremes
+
+	fn reaching <'a> (
+		& 'a self,
+		clues_line: & 'a CluesLine,
+		cell_index: LineSize,
+	) -> impl Iterator <Item = usize> + 'a {
+
+		clues_line.iter ().enumerate ().filter (
+			move |& (clue_index, & size)|
+				self.left [clue_index] <= cell_index
+					&& cell_index < self.right [clue_index] + size,
+		).map (
+			|(clue_index, _)| clue_index,
+		)
+
+	}
+
+	fn only_reaching (
+		& self,
+		clues_line: & CluesLine,
+		cell_index: LineSize,
+	) -> Option <usize> {
+
+		let mut reaching = self.reaching (clues_line, cell_index);
+
+		match (reaching.next (), reaching.next ()) {
+			(Some (clue_index), None) => Some (clue_index),
+			_ => None,
+		}
+
+	}
+
+	// runs of boxes, as start and end
+
+	fn runs (& self) -> Vec <(LineSize, LineSize)> {
+		self.segments (Cell::is_filled)
+	}
+
+	// runs of cells which aren't spaces, as start and end
+
+	fn gaps (& self) -> Vec <(LineSize, LineSize)> {
+		self.segments (Cell::can_fill)
+	}
+
+	fn segments (
+		& self,
+		predicate: fn (Cell) -> bool,
+	) -> Vec <(LineSize, LineSize)> {
+
+		let mut segments = Vec::new ();
+		let mut start = None;
+
+		for cell_index in 0 ..= self.line.len () {
+
+			let inside = cell_index < self.line.len () && predicate (self.line [cell_index]);
+
+			match (start, inside) {
+				(None, true) => start = Some (cell_index),
+				(Some (segment_start), false) => {
+					segments.push ((segment_start, cell_index));
+					start = None;
+				},
+				_ => (),
+			}
+
+		}
+
+		segments
+
+	}
+
+}
+
+impl LineSolve for RuleEngine {
+
+	fn solve_line <
+		LineIter: IntoIterator <Item = Cell>,
+	> (
+		& mut self,
+		line_iter: LineIter,
+		clues_line: & CluesLine,
+	) -> Option <& Line> {
+
+		self.apply_rules (line_iter, clues_line) ?;
+
+		Some (& self.line)
+
+	}
+
+}
+
+#[ cfg (test) ]
+mod tests {
+
+	use std::str::FromStr;
+
+	use super::*;
+
+	fn apply_rule (rule: Rule, line: & str, clues_line: & CluesLine) -> Option <LineBuf> {
+
+		let mut rule_engine = RuleEngine::new ();
+
+		rule_engine.apply_rule (rule, & LineBuf::from_str (line).unwrap (), clues_line) ?;
+
+		Some (rule_engine.line ().to_owned ())
+
+	}
+
+	#[ test ]
+	fn test_rules () {
+
+		let cases: Vec <(Rule, & str, CluesLine, & str)> = vec! [
+			(Rule::SimpleBoxes, "----------", vec! [ 8 ], "--######--"),
+			(Rule::SimpleBoxes, "----------", vec! [ 3, 4 ], "--#---##--"),
+			(Rule::SimpleSpaces, "-----#----", vec! [ 2 ], "    -#-   "),
+			(Rule::Forcing, "-- ------- --", vec! [ 3, 3 ], "   -------   "),
+			(Rule::Glue, " #--------", vec! [ 3, 2 ], " ###------"),
+			(Rule::Glue, "-------#  ", vec! [ 3, 3 ], "-----###  "),
+			(Rule::Joining, "--#--#----", vec! [ 5 ], "--####----"),
+			(Rule::Splitting, "--#-#-----", vec! [ 1, 1, 2 ], "--# #-----"),
+			(Rule::Mercury, "-#--------", vec! [ 4 ], "-###------"),
+			(Rule::Punctuating, "---###----", vec! [ 1, 3 ], "-- ### ---"),
+		];
+
+		for (rule, line, clues_line, expected) in cases {
+
+			assert_eq! (
+				apply_rule (rule, line, & clues_line),
+				Some (LineBuf::from_str (expected).unwrap ()),
+				"{} {:?} {:?}",
+				rule,
+				line,
+				clues_line,
+			);
+
+		}
+
+	}
+
+	#[ test ]
+	fn test_apply_rules_deductions () {
+
+		let mut rule_engine = RuleEngine::new ();
+
+		let deductions = rule_engine.apply_rules (
+			& LineBuf::from_str ("----------").unwrap (),
+			& vec! [ 8 ],
+		).unwrap ().to_vec ();
+
+		assert_eq! (deductions.len (), 6);
+
+		assert! (deductions.iter ().all (
+			|deduction| deduction.rule == Rule::SimpleBoxes && deduction.cell == Cell::FILLED,
+		));
+
+		let deductions = rule_engine.apply_rules (
+			& LineBuf::from_str ("---- -----").unwrap (),
+			& vec! [ 5 ],
+		).unwrap ().to_vec ();
+
+		assert_eq! (rule_engine.line ().to_owned (), LineBuf::from_str ("     #####").unwrap ());
+		assert! (deductions.iter ().any (|deduction| deduction.rule == Rule::SimpleSpaces));
+
+		assert_eq! (
+			rule_engine.apply_rules (
+				& LineBuf::from_str ("-#- -#-").unwrap (),
+				& vec! [ 3 ],
+			),
+			None,
+		);
+
+	}
+
+	// anything the rules find must agree with the reference solver, and they
+	// can only find a contradiction where there is one
+
+	#[ test ]
+	fn test_rules_sound () {
+
+		let mut rule_engine = RuleEngine::new ();
+		let mut reference_solver = BruteForceLineSolver::new ();
+
+		for line_size in 0 .. 8 {
+
+			let mut clues_lines: Vec <CluesLine> = (0 .. 1 << line_size).map (
+				|solution: u32| {
+					let mut clues_line = Vec::new ();
+					let mut run = 0;
+					for cell_index in 0 .. line_size + 1 {
+						if cell_index < line_size && solution & (1 << cell_index) != 0 {
+							run += 1;
+						} else if run > 0 {
+							clues_line.push (run);
+							run = 0;
+						}
+					}
+					clues_line
+				},
+			).collect ();
+
+			clues_lines.sort ();
+			clues_lines.dedup ();
+
+			for line_index in 0 .. 3_u32.pow (line_size) {
+
+				let line: LineBuf = (0 .. line_size).map (
+					|cell_index| match line_index / 3_u32.pow (cell_index) % 3 {
+						0 => Cell::UNKNOWN,
+						1 => Cell::EMPTY,
+						_ => Cell::FILLED,
+					},
+				).collect ();
+
+				for clues_line in clues_lines.iter () {
+
+					let expected = reference_solver.solve_line (& line, clues_line)
+						.map (ToOwned::to_owned);
+
+					let actual = rule_engine.solve_line (& line, clues_line)
+						.map (ToOwned::to_owned);
+
+					match (actual, expected) {
+						(_, None) => (),
+						(Some (actual), Some (expected)) => assert! (
+							actual.iter ().zip (expected.iter ()).all (
+								|(actual, expected)| actual.is_unknown () || actual == expected,
+							),
+							"{:?} {:?}: {:?} {:?} {:?}",
+							line,
+							clues_line,
+							actual,
+							expected,
+							rule_engine.deductions (),
+						),
+						(None, Some (expected)) => panic! (
+							"{:?} {:?}: no solution, expected {:?}",
+							line,
+							clues_line,
+							expected,
+						),
+					}
+
+				}
+
+			}
+
+		}
+
+	}
+
+	#[ test ]
+	fn test_rules_grid () {
+
+		let clues = load_sample ("wikipedia");
+
+		let mut grid_solver = GridSolver::with_line_solver (
+			unknown_grid (& clues),
+			clues,
+			Default::default (),
+			RuleEngine::new (),
+		).unwrap ();
+
+		finish (& mut grid_solver);
+
+		assert! (grid_solver.is_solved ());
+
+	}
+
+}
@@ -0,0 +1,264 @@
+use crate::data::*;
+use crate::solver::*;
+
+#[ derive (Clone, Copy, Debug, Eq, PartialEq) ]
+pub enum SolutionCount {
+	Exactly (usize),
+	AtLeast (usize),
+}
+
+impl SolutionCount {
+
+	pub fn is_unique (self) -> bool {
+		self == SolutionCount::Exactly (1)
+	}
+
+}
+
+pub fn find_solutions (
+	clues: & Clues,
+) -> Solutions {
+
+	// the grid is made to fit the clues, so this can't fail
+
+	Solutions::new (
+		Grid::with_colours (clues.num_rows (), clues.num_cols (), clues.num_colours ()),
+		clues.clone (),
+	).unwrap ()
+
+}
+
+// returns at most limit solutions. the count is exact if there are no more than
+// that, and AtLeast (limit) otherwise, so we look for one extra solution to tell
+// the two apart. a limit of zero still finds out whether there are any at all.
+
+pub fn count_solutions (
+	clues: & Clues,
+	limit: usize,
+) -> (SolutionCount, Vec <Grid>) {
+
+	let mut solutions: Vec <Grid> = find_solutions (clues).take (limit + 1).collect ();
+
+	if solutions.len () > limit {
+		solutions.truncate (limit);
+		(SolutionCount::AtLeast (limit), solutions)
+	} else {
+		(SolutionCount::Exactly (solutions.len ()), solutions)
+	}
+
+}
+
+pub struct Solutions {
+	grid_solver: GridSolver,
+	started: bool,
+	exhausted: bool,
+}
+
+impl Solutions {
+
+	pub fn new (
+		grid: Grid,
+		clues: Clues,
+	) -> Result <Solutions, GridSolverError> {
+
+		Ok (Solutions {
+			grid_solver: GridSolver::with_config (
+				grid,
+				clues,
+				GridSolverConfig {
+					backtracking: true,
+					probing: true,
+					.. Default::default ()
+				},
+			) ?,
+			started: false,
+			exhausted: false,
+		})
+
+	}
+
+	pub fn is_exhausted (& self) -> bool {
+		self.exhausted
+	}
+
+	pub fn stats (& self) -> & GridSolverStats {
+		self.grid_solver.stats ()
+	}
+
+	pub fn release (self) -> (Clues, Grid) {
+		self.grid_solver.release ()
+	}
+
+}
+
+impl Iterator for Solutions {
+
+	type Item = Grid;
+
+	fn next (& mut self) -> Option <Grid> {
+
+		if self.exhausted {
+			return None;
+		}
+
+		if self.started && self.grid_solver.resume ().is_none () {
+			self.exhausted = true;
+			return None;
+		}
+
+		self.started = true;
+
+		while self.grid_solver.next ().is_some () { }
+
+		if ! self.grid_solver.is_solved () {
+			self.exhausted = true;
+			return None;
+		}
+
+		// if there are no guesses to revisit this is the last solution
+
+		if self.grid_solver.depth () == 0 {
+			self.exhausted = true;
+		}
+
+		Some (self.grid_solver.grid ().clone ())
+
+	}
+
+}
+
+#[ cfg (test) ]
+mod tests {
+
+	use std::str::FromStr;
+
+	use super::*;
+
+	#[ test ]
+	fn test_find_solutions () {
+
+		let clues = load_clues ("rows\n1\n1\ncols\n1\n1\n");
+		let mut solutions: Vec <Grid> = find_solutions (& clues).collect ();
+
+		let mut expected = vec! [
+			Grid::new (2, 2),
+			Grid::new (2, 2),
+		];
+
+		for (grid, row_index) in expected.iter_mut ().zip ([ 0, 1 ]) {
+			grid.set_row (row_index, & LineBuf::from_str ("# ").unwrap ());
+			grid.set_row (1 - row_index, & LineBuf::from_str (" #").unwrap ());
+		}
+
+		solutions.sort_by_key (|grid| grid [(0, 0)].is_filled ());
+		expected.sort_by_key (|grid| grid [(0, 0)].is_filled ());
+
+		assert_eq! (solutions, expected);
+
+	}
+
+	#[ test ]
+	fn test_find_solutions_samples () {
+
+		for name in [ "camel", "heart", "wikipedia" ] {
+
+			let clues = load_sample (name);
+
+			let solutions: Vec <Grid> = find_solutions (& clues).collect ();
+
+			assert_eq! (solutions.len (), 1, "{}", name);
+			assert! (solutions [0].is_solved (), "{}", name);
+
+		}
+
+	}
+
+	#[ test ]
+	fn test_find_solutions_colours () {
+
+		let clues = load_sample ("colours");
+
+		let solutions: Vec <Grid> = find_solutions (& clues).collect ();
+
+		assert_eq! (solutions.len (), 1);
+		assert! (clues.check (& solutions [0]).is_ok ());
+
+		// two solutions, which differ in the colour of the first cell
+
+		let clues = load_clues (
+			"colours\nr ff0000\nb 0000ff\nrows\n1b 1r\n1b 1r\ncols\n1b\n1b\n2r\n",
+		);
+
+		let (count, solutions) = count_solutions (& clues, 5);
+
+		assert_eq! (count, SolutionCount::Exactly (2));
+		assert! (solutions.iter ().all (|solution| clues.check (solution).is_ok ()));
+		assert_ne! (solutions [0], solutions [1]);
+
+	}
+
+	#[ test ]
+	fn test_count_solutions_unique () {
+
+		let clues = load_clues ("rows\n2\n1 1\n1\n1\ncols\n2\n2\n1\n1\n");
+		let (count, solutions) = count_solutions (& clues, 2);
+
+		assert_eq! (count, SolutionCount::Exactly (1));
+		assert! (count.is_unique ());
+		assert_eq! (solutions.len (), 1);
+		assert! (solutions [0].is_solved ());
+
+	}
+
+	#[ test ]
+	fn test_count_solutions_ambiguous () {
+
+		let clues = load_clues ("rows\n1\n1\n1\ncols\n1\n1\n1\n");
+
+		let (count, solutions) = count_solutions (& clues, 2);
+		assert_eq! (count, SolutionCount::AtLeast (2));
+		assert_eq! (solutions.len (), 2);
+		assert_ne! (solutions [0], solutions [1]);
+
+		let (count, solutions) = count_solutions (& clues, 10);
+		assert_eq! (count, SolutionCount::Exactly (6));
+		assert_eq! (solutions.len (), 6);
+
+	}
+
+	#[ test ]
+	fn test_count_solutions_limit () {
+
+		let clues = load_clues ("rows\n1\n1\n1\ncols\n1\n1\n1\n");
+
+		let (count, solutions) = count_solutions (& clues, 6);
+		assert_eq! (count, SolutionCount::Exactly (6));
+		assert_eq! (solutions.len (), 6);
+
+		let (count, solutions) = count_solutions (& clues, 5);
+		assert_eq! (count, SolutionCount::AtLeast (5));
+		assert_eq! (solutions.len (), 5);
+
+		let (count, solutions) = count_solutions (& clues, 0);
+		assert_eq! (count, SolutionCount::AtLeast (0));
+		assert! (solutions.is_empty ());
+
+		let unsolvable = load_clues ("rows\n2\n2\ncols\n1\n1\n");
+		let (count, solutions) = count_solutions (& unsolvable, 0);
+		assert_eq! (count, SolutionCount::Exactly (0));
+		assert! (solutions.is_empty ());
+
+	}
+
+	#[ test ]
+	fn test_count_solutions_none () {
+
+		let clues = load_clues ("rows\n2\n2\ncols\n1\n1\n");
+		let (count, solutions) = count_solutions (& clues, 2);
+
+		assert_eq! (count, SolutionCount::Exactly (0));
+		assert! (solutions.is_empty ());
+
+	}
+
+}
@@ -1,18 +1,36 @@
+use std::cmp;
+use std::fmt;
 use std::iter;
 use std::mem;
 
 use crate::data::*;
 use crate::solver::*;
 
-pub struct GridSolver {
+pub struct GridSolver <Solver: LineSolve = LineSolver> {
 
 	grid: Grid,
 	clues: Clues,
+	config: GridSolverConfig,
 
 	stats: GridSolverStats,
 	changed_rows: Vec <bool>,
 	changed_cols: Vec <bool>,
-	line_solver: LineSolver,
+	row_changes: Vec <LineSize>,
+	col_changes: Vec <LineSize>,
+	row_slack: Vec <LineSize>,
+	col_slack: Vec <LineSize>,
+	line_solver: CachedLineSolver <Solver>,
+	cache_stats_base: (usize, usize),
+	cache_counters_base: (usize, usize),
+	colour_line_solver: Option <ColourLineSolver>,
+	solved_line: LineBuf,
+	worker_line_solvers: Vec <Solver>,
+	batch: Vec <(LineRef, Option <LineBuf>)>,
+	guesses: Vec <GuessFrame>,
+	probed: bool,
+	probed_cells: Vec <(LineSize, LineSize, Cell)>,
+	proof: Option <Proof>,
+	proof_step: usize,
 
 	state: State,
 	vertical: bool,
@@ -21,16 +39,84 @@ pub struct GridSolver {
 
 }
 
-enum State {
+// everything about a solve which changes as it progresses, so it can be
+// restored later to undo or branch from this point, along with the config
+// which the guesses and probes depend on
+
+#[ derive (Clone, Debug) ]
+pub struct GridSolverSnapshot {
+	pub (super) backtracking: bool,
+	pub (super) probing: bool,
+	pub (super) grid: Grid,
+	pub (super) stats: GridSolverStats,
+	pub (super) changed_rows: Vec <bool>,
+	pub (super) changed_cols: Vec <bool>,
+	pub (super) row_changes: Vec <LineSize>,
+	pub (super) col_changes: Vec <LineSize>,
+	pub (super) solved_line: LineBuf,
+	pub (super) guesses: Vec <GuessFrame>,
+	pub (super) probed: bool,
+	pub (super) probed_cells: Vec <(LineSize, LineSize, Cell)>,
+	pub (super) proof: Option <Proof>,
+	pub (super) proof_step: usize,
+	pub (super) state: State,
+	pub (super) vertical: bool,
+	pub (super) index: LineSize,
+	pub (super) index_changed: bool,
+}
+
+#[ derive (Clone, Copy, Debug) ]
+pub (super) enum State {
 	Scanning,
 	Solving (LineSize),
+	Probing (LineSize, LineSize),
 	Complete,
+	Contradiction,
+	Stuck,
 }
 
-#[ derive (Clone, Copy, Debug) ]
+#[ derive (Clone, Debug) ]
+pub (super) struct GuessFrame {
+	pub (super) grid: Grid,
+	pub (super) row_index: LineSize,
+	pub (super) col_index: LineSize,
+}
+
+#[ derive (Clone, Debug) ]
+pub struct GridSolverConfig {
+	pub backtracking: bool,
+	pub probing: bool,
+	pub scan_order: ScanOrder,
+	pub line_cache_size: usize,
+	pub threads: usize,
+	pub record_proof: bool,
+}
+
+#[ derive (Clone, Copy, Debug, Default, Eq, PartialEq) ]
+pub enum ScanOrder {
+
+	// all rows then all columns, in order
+
+	#[ default ]
+	RoundRobin,
+
+	// the changed line with the most newly solved cells relative to its slack
+
+	Priority,
+
+}
+
+#[ derive (Clone, Copy, Debug, Default) ]
 pub struct GridSolverStats {
 	pub grid_iterations: usize,
 	pub line_iterations: usize,
+	pub guesses: usize,
+	pub backtracks: usize,
+	pub max_depth: usize,
+	pub probes: usize,
+	pub probed_cells: usize,
+	pub cache_hits: usize,
+	pub cache_misses: usize,
 }
 
 #[ derive (Debug) ]
@@ -44,6 +130,21 @@ pub enum GridSolverEvent {
 	SolvedCol (LineSize),
 	SolvedGrid,
 
+	Probe (LineSize, LineSize),
+	ProbedCell (LineSize, LineSize, Cell),
+
+	Guess (LineSize, LineSize, Cell),
+	Backtrack (LineSize, LineSize, Cell),
+
+	Contradiction (LineRef, CluesLine),
+	Stuck,
+
+}
+
+#[ derive (Clone, Copy, Debug, Eq, PartialEq) ]
+pub enum LineRef {
+	Row (LineSize),
+	Col (LineSize),
 }
 
 impl GridSolver {
@@ -51,32 +152,123 @@ impl GridSolver {
 	pub fn new (
 		grid: Grid,
 		clues: Clues,
-	) -> GridSolver {
+	) -> Result <GridSolver, GridSolverError> {
+
+		GridSolver::with_config (
+			grid,
+			clues,
+			Default::default (),
+		)
+
+	}
+
+	pub fn with_config (
+		grid: Grid,
+		clues: Clues,
+		config: GridSolverConfig,
+	) -> Result <GridSolver, GridSolverError> {
+
+		GridSolver::with_line_solver (
+			grid,
+			clues,
+			config,
+			Default::default (),
+		)
+
+	}
+
+}
+
+impl <Solver: LineSolve + Default + Send> GridSolver <Solver> {
+
+	pub fn with_line_solver (
+		grid: Grid,
+		clues: Clues,
+		config: GridSolverConfig,
+		line_solver: Solver,
+	) -> Result <GridSolver <Solver>, GridSolverError> {
+
+		if (grid.num_rows (), grid.num_cols ()) != (clues.num_rows (), clues.num_cols ()) {
+			return Err (GridSolverError::Size {
+				grid: (grid.num_rows (), grid.num_cols ()),
+				clues: (clues.num_rows (), clues.num_cols ()),
+			});
+		}
+
+		if grid.num_colours () != clues.num_colours () {
+			return Err (GridSolverError::Colours {
+				grid: grid.num_colours (),
+				clues: clues.num_colours (),
+			});
+		}
+
+		let changed_rows = iter::repeat_n (true, grid.num_rows () as usize).collect ();
+
+		let changed_cols = iter::repeat_n (true, grid.num_cols () as usize).collect ();
 
-		let changed_rows = iter::repeat (true).take (
-			grid.num_rows () as usize,
+		let line_solver = CachedLineSolver::new (
+			line_solver,
+			config.line_cache_size,
+		);
+
+		let row_changes = vec! [0; grid.num_rows () as usize];
+		let col_changes = vec! [0; grid.num_cols () as usize];
+
+		let row_slack = clues.rows ().map (
+			|clues_line| clues_line_slack (clues_line, grid.num_cols ()),
 		).collect ();
 
-		let changed_cols = iter::repeat (true).take (
-			grid.num_cols () as usize,
+		let col_slack = clues.cols ().map (
+			|clues_line| clues_line_slack (clues_line, grid.num_rows ()),
 		).collect ();
 
-		GridSolver {
+		let proof = if config.record_proof {
+			Some (Proof::new (grid.num_rows (), grid.num_cols ()))
+		} else {
+			None
+		};
+
+		// coloured clues always use the colour line solver, since the other
+		// solvers only know about filled and empty cells
+
+		let colour_line_solver = if clues.is_coloured () {
+			Some (ColourLineSolver::new ())
+		} else {
+			None
+		};
+
+		Ok (GridSolver {
 
 			grid: grid,
 			clues: clues,
+			config: config,
 
 			stats: GridSolverStats::new (),
+			row_changes: row_changes,
+			col_changes: col_changes,
+			row_slack: row_slack,
+			col_slack: col_slack,
 			changed_rows: changed_rows,
 			changed_cols: changed_cols,
-			line_solver: Default::default (),
+			line_solver: line_solver,
+			cache_stats_base: (0, 0),
+			cache_counters_base: (0, 0),
+			colour_line_solver: colour_line_solver,
+			solved_line: Default::default (),
+			worker_line_solvers: Vec::new (),
+			batch: Vec::new (),
+			guesses: Vec::new (),
+			probed: false,
+			probed_cells: Vec::new (),
+			proof: proof,
+			proof_step: 0,
 
 			vertical: false,
 			index: 0,
 			index_changed: true,
 			state: State::Scanning,
 
-		}
+		})
 
 	}
 
@@ -88,6 +280,10 @@ impl GridSolver {
 		& self.clues
 	}
 
+	pub fn config (& self) -> & GridSolverConfig {
+		& self.config
+	}
+
 	pub fn grid (& self) -> & Grid {
 		& self.grid
 	}
@@ -96,6 +292,95 @@ impl GridSolver {
 		& self.stats
 	}
 
+	pub fn proof (& self) -> Option <& Proof> {
+		self.proof.as_ref ()
+	}
+
+	pub fn snapshot (& self) -> GridSolverSnapshot {
+
+		GridSolverSnapshot {
+			backtracking: self.config.backtracking,
+			probing: self.config.probing,
+			grid: self.grid.clone (),
+			stats: self.stats,
+			changed_rows: self.changed_rows.clone (),
+			changed_cols: self.changed_cols.clone (),
+			row_changes: self.row_changes.clone (),
+			col_changes: self.col_changes.clone (),
+			solved_line: self.solved_line.clone (),
+			guesses: self.guesses.clone (),
+			probed: self.probed,
+			probed_cells: self.probed_cells.clone (),
+			proof: self.proof.clone (),
+			proof_step: self.proof_step,
+			state: self.state,
+			vertical: self.vertical,
+			index: self.index,
+			index_changed: self.index_changed,
+		}
+
+	}
+
+	// the snapshot must come from a solver for the same clues, and leaves the
+	// config alone, so callers decide whether to use its saved settings
+
+	pub fn restore (& mut self, snapshot: GridSolverSnapshot) {
+
+		assert_eq! (snapshot.grid.num_rows (), self.clues.num_rows ());
+		assert_eq! (snapshot.grid.num_cols (), self.clues.num_cols ());
+		assert_eq! (snapshot.grid.num_colours (), self.clues.num_colours ());
+
+		self.grid = snapshot.grid;
+		self.stats = snapshot.stats;
+		self.changed_rows = snapshot.changed_rows;
+		self.changed_cols = snapshot.changed_cols;
+		self.row_changes = snapshot.row_changes;
+		self.col_changes = snapshot.col_changes;
+		self.solved_line = snapshot.solved_line;
+		self.guesses = snapshot.guesses;
+		self.probed = snapshot.probed;
+		self.probed_cells = snapshot.probed_cells;
+		self.proof = snapshot.proof;
+
+		// cells solved before the snapshot count as given if it has no proof
+
+		if self.proof.is_none () && self.config.record_proof {
+			self.proof = Some (Proof::new (self.clues.num_rows (), self.clues.num_cols ()));
+		}
+
+		self.proof_step = snapshot.proof_step;
+		self.state = snapshot.state;
+		self.vertical = snapshot.vertical;
+		self.index = snapshot.index;
+		self.index_changed = snapshot.index_changed;
+
+		// the cache keeps its own counters, so count on from the snapshot's
+
+		self.cache_stats_base = (self.stats.cache_hits, self.stats.cache_misses);
+		self.cache_counters_base = (self.line_solver.hits (), self.line_solver.misses ());
+
+		self.batch.clear ();
+
+	}
+
+	pub fn line_solver (& self) -> & Solver {
+		self.line_solver.inner ()
+	}
+
+	fn update_cache_stats (& mut self) {
+
+		self.stats.cache_hits =
+			self.cache_stats_base.0 + self.line_solver.hits () - self.cache_counters_base.0;
+
+		self.stats.cache_misses =
+			self.cache_stats_base.1 + self.line_solver.misses () - self.cache_counters_base.1;
+
+	}
+
+	pub fn depth (& self) -> usize {
+		self.guesses.len ()
+	}
+
 	fn advance (& mut self) {
 
 		let max_index = if ! self.vertical {
@@ -137,34 +422,102 @@ impl GridSolver {
 
 	}
 
+	fn find_changed_line (& mut self) -> bool {
+
+		match self.config.scan_order {
+			ScanOrder::RoundRobin => self.find_changed_line_round_robin (),
+			ScanOrder::Priority => self.find_changed_line_priority (),
+		}
+
+	}
+
+	// solved lines are still solved again, since a guess or a given cell can
+	// complete a line which doesn't match its clues, and only solving it finds
+	// the contradiction
+
+	fn find_changed_line_round_robin (& mut self) -> bool {
+
+		let num_lines = self.grid.num_rows () as usize
+			+ self.grid.num_cols () as usize;
+
+		for _ in 0 .. num_lines {
+
+			if self.get_line_changed () {
+				return true;
+			}
+
+			self.advance ();
+
+		}
+
+		false
+
+	}
+
+	fn find_changed_line_priority (& mut self) -> bool {
+
+		let rows = self.changed_rows.iter ().enumerate ().filter (
+			|& (_, & changed)| changed,
+		).map (
+			|(index, _)| (false, index, self.row_changes [index], self.row_slack [index]),
+		);
+
+		let cols = self.changed_cols.iter ().enumerate ().filter (
+			|& (_, & changed)| changed,
+		).map (
+			|(index, _)| (true, index, self.col_changes [index], self.col_slack [index]),
+		);
+
+		// favour lines with lots of newly solved cells and not much slack
+
+		let best = rows.chain (cols).min_by_key (
+			|& (_, _, changes, slack)| cmp::Reverse (
+				(changes as usize + 1) * 64 / (slack as usize + 1),
+			),
+		);
+
+		let (vertical, index) = match best {
+			Some ((vertical, index, _, _)) => (vertical, index as LineSize),
+			None => return false,
+		};
+
+		if vertical != self.vertical || index != self.index {
+			self.vertical = vertical;
+			self.index = index;
+			self.index_changed = true;
+		}
+
+		true
+
+	}
+
 	fn unset_line_changed (& mut self) {
 
 		if ! self.vertical {
-			self.changed_rows [self.index as usize] = false
+			self.changed_rows [self.index as usize] = false;
+			self.row_changes [self.index as usize] = 0;
 		} else {
-			self.changed_cols [self.index as usize] = false
+			self.changed_cols [self.index as usize] = false;
+			self.col_changes [self.index as usize] = 0;
 		}
 
 	}
 
-	fn get_clues (& self) -> & CluesLine {
+	fn get_clues_line (& self, line_ref: LineRef) -> & CluesLine {
 
-		if ! self.vertical {
-			self.clues.row (self.index)
-		} else {
-			self.clues.col (self.index)
+		match line_ref {
+			LineRef::Row (index) => self.clues.row (index),
+			LineRef::Col (index) => self.clues.col (index),
 		}
 
 	}
 
-	fn get_line <'a> (
-		& 'a self,
-	) -> GridIter <'a> {
+	fn get_clues (& self) -> & CluesLine {
 
 		if ! self.vertical {
-			self.grid.row (self.index)
+			self.clues.row (self.index)
 		} else {
-			self.grid.col (self.index)
+			self.clues.col (self.index)
 		}
 
 	}
@@ -181,150 +534,1223 @@ impl GridSolver {
 
 	fn set_cell (& mut self, cell_index: LineSize, cell: Cell) {
 
+		if let Some (ref mut proof) = self.proof {
+			if ! self.vertical {
+				proof.set_cell (self.index, cell_index, self.proof_step);
+			} else {
+				proof.set_cell (cell_index, self.index, self.proof_step);
+			}
+		}
+
 		if ! self.vertical {
 			self.grid [(self.index, cell_index)] = cell;
-			self.changed_cols [cell_index as usize] = true
+			self.changed_cols [cell_index as usize] = true;
+			self.col_changes [cell_index as usize] += 1;
+			self.invalidate_batch (LineRef::Col (cell_index));
 		} else {
 			self.grid [(cell_index, self.index)] = cell;
 			self.changed_rows [cell_index as usize] = true;
+			self.row_changes [cell_index as usize] += 1;
+			self.invalidate_batch (LineRef::Row (cell_index));
 		}
 
 	}
 
-	fn is_complete (& self) -> bool {
-		match self.state {
-			State::Complete => true,
-			_ => false,
+	// sets a cell for the proof step last added, for probes, guesses and
+	// backtracks
+
+	fn set_grid_cell (
+		& mut self,
+		row_index: LineSize,
+		col_index: LineSize,
+		cell: Cell,
+	) {
+
+		if let Some (ref mut proof) = self.proof {
+			proof.set_cell (row_index, col_index, self.proof_step);
+		}
+
+		self.grid [(row_index, col_index)] = cell;
+		self.changed_rows [row_index as usize] = true;
+		self.changed_cols [col_index as usize] = true;
+		self.row_changes [row_index as usize] += 1;
+		self.col_changes [col_index as usize] += 1;
+		self.index_changed = true;
+		self.probed = false;
+
+		self.invalidate_batch (LineRef::Row (row_index));
+		self.invalidate_batch (LineRef::Col (col_index));
+
+	}
+
+	fn invalidate_batch (& mut self, line_ref: LineRef) {
+
+		if self.batch.is_empty () {
+			return;
 		}
+
+		self.batch.retain (|& (batch_line_ref, _)| batch_line_ref != line_ref);
+
 	}
 
-	fn is_solving (& self) -> bool {
-		match self.state {
-			State::Solving (..) => true,
-			_ => false,
+	// solve every changed line in the current direction at once, the results
+	// are the same as solving them one by one since they are independent
+
+	fn solve_batch (& mut self) {
+
+		let line_refs: Vec <LineRef> = if ! self.vertical {
+			self.changed_rows.iter ().enumerate ().filter (
+				|& (_, & changed)| changed,
+			).map (
+				|(index, _)| LineRef::Row (index as LineSize),
+			).collect ()
+		} else {
+			self.changed_cols.iter ().enumerate ().filter (
+				|& (_, & changed)| changed,
+			).map (
+				|(index, _)| LineRef::Col (index as LineSize),
+			).collect ()
+		};
+
+		// lines in the shared cache are answered from it, and only the rest are
+		// handed to the workers, whose results then go back into the cache
+
+		let mut batch = Vec::new ();
+		let mut missed_line_refs = Vec::new ();
+
+		for line_ref in line_refs {
+			match self.lookup_line_ref (line_ref) {
+				Some (result) => batch.push ((line_ref, result)),
+				None => missed_line_refs.push (line_ref),
+			}
+		}
+
+		self.worker_line_solvers.resize_with (
+			self.config.threads,
+			Default::default,
+		);
+
+		let solved = solve_lines_parallel (
+			& self.grid,
+			& self.clues,
+			& missed_line_refs,
+			& mut self.worker_line_solvers,
+		);
+
+		for (line_ref, result) in solved.iter () {
+			match * line_ref {
+				LineRef::Row (index) => self.line_solver.store (
+					self.grid.row (index), self.clues.row (index), result.clone ()),
+				LineRef::Col (index) => self.line_solver.store (
+					self.grid.col (index), self.clues.col (index), result.clone ()),
+			}
 		}
+
+		batch.extend (solved);
+		self.batch = batch;
+
+		self.update_cache_stats ();
+
 	}
 
-	fn next_cell (& mut self) -> Option <(LineSize, Cell)> {
+	fn lookup_line_ref (& mut self, line_ref: LineRef) -> Option <Option <LineBuf>> {
+		match line_ref {
+			LineRef::Row (index) =>
+				self.line_solver.lookup (self.grid.row (index), self.clues.row (index)),
+			LineRef::Col (index) =>
+				self.line_solver.lookup (self.grid.col (index), self.clues.col (index)),
+		}
+	}
 
-		match self.state {
+	fn solve_current_line (& mut self) -> bool {
 
-			State::Solving (ref mut cell_index) => {
+		let line_ref = self.get_line_ref ();
 
-				if let Some (cell) = self.line_solver.next () {
+		// cells solved from this line rely on the ones already known in it,
+		// including any narrowed down to some of the colours
 
-					let result = (* cell_index, cell);
-					* cell_index += 1;
-					Some (result)
+		let unknown = Cell::unknown (self.clues.num_colours ());
 
-				} else {
-					None
-				}
+		if let Some (ref mut proof) = self.proof {
 
-			},
+			let line_size = match line_ref {
+				LineRef::Row (_) => self.grid.num_cols (),
+				LineRef::Col (_) => self.grid.num_rows (),
+			};
 
-			_ => panic! (),
+			let depends = (0 .. line_size).map (
+				|cell_index| match line_ref {
+					LineRef::Row (row_index) => (row_index, cell_index),
+					LineRef::Col (col_index) => (cell_index, col_index),
+				},
+			).filter (
+				|& position| self.grid [position] != unknown,
+			).collect ();
+
+			self.proof_step = proof.add_step (ProofStep::Line (line_ref, depends));
+
+		}
+
+		if let Some (ref mut colour_line_solver) = self.colour_line_solver {
+
+			let (line_iter, clues_line, colours) = if ! self.vertical {
+				(self.grid.row (self.index), self.clues.row (self.index), self.clues.row_colours (self.index))
+			} else {
+				(self.grid.col (self.index), self.clues.col (self.index), self.clues.col_colours (self.index))
+			};
+
+			return match colour_line_solver.solve_colour_line (line_iter, clues_line, colours) {
+				Some (line) => {
+					let solved_line = mem::take (& mut self.solved_line);
+					self.solved_line = solved_line.into_copy_of (line.iter ());
+					true
+				},
+				None => false,
+			};
+
+		}
 
+		if self.config.threads > 1
+		&& ! self.batch.iter ().any (|& (batch_line_ref, _)| batch_line_ref == line_ref) {
+			self.solve_batch ();
 		}
 
+		if let Some (position) = self.batch.iter ().position (
+			|& (batch_line_ref, _)| batch_line_ref == line_ref,
+		) {
+
+			let (_, result) = self.batch.swap_remove (position);
+
+			return match result {
+				Some (line) => {
+					self.solved_line = line;
+					true
+				},
+				None => false,
+			};
+
+		}
+
+		let (line_iter, clues_line) = if ! self.vertical {
+			(self.grid.row (self.index), self.clues.row (self.index))
+		} else {
+			(self.grid.col (self.index), self.clues.col (self.index))
+		};
+
+		let solved = match self.line_solver.solve_line (line_iter, clues_line) {
+			Some (line) => {
+				let solved_line = mem::take (& mut self.solved_line);
+				self.solved_line = solved_line.into_copy_of (line.iter ());
+				true
+			},
+			None => false,
+		};
+
+		self.update_cache_stats ();
+
+		solved
+
 	}
 
-	pub fn next (
+	fn probe (
 		& mut self,
-	) -> Option <GridSolverEvent> {
+		row_index: LineSize,
+		col_index: LineSize,
+	) -> Result <(), LineRef> {
 
-		if self.is_complete () {
-			return None;
+		self.stats.probes += 1;
+
+		let values = cell_values (self.grid [(row_index, col_index)], self.clues.num_colours ());
+
+		let results: Vec <Result <Grid, LineRef>> = values.iter ().map (
+			|& value| self.probe_branch (row_index, col_index, value),
+		).collect ();
+
+		let grids: Vec <& Grid> = results.iter ().filter_map (
+			|result| result.as_ref ().ok (),
+		).collect ();
+
+		if grids.is_empty () {
+			return Err (results.into_iter ().next ().unwrap ().unwrap_err ());
 		}
 
-		loop {
+		// values which lead to a contradiction are ruled out
 
-			if self.is_solving () {
+		if grids.len () < values.len () {
 
-				if let Some ((cell_index, cell)) = self.next_cell () {
+			let cell = values.iter ().zip (results.iter ()).filter (
+				|(_, result)| result.is_ok (),
+			).fold (
+				Cell::ERROR,
+				|cell, (& value, _)| cell.union (value),
+			);
 
-					if cell == self.get_cell (cell_index) {
-						continue;
-					}
+			self.probed_cells.push ((row_index, col_index, cell));
 
-					self.set_cell (cell_index, cell);
+			return Ok (());
 
-					return Some (
-						if ! self.vertical {
-							GridSolverEvent::SolvedCell (self.index, cell_index)
-						} else {
-							GridSolverEvent::SolvedCell (cell_index, self.index)
-						}
-					);
+		}
 
-				}
+		// otherwise cells are forced to whatever they could be in any branch
 
-				self.state = State::Scanning;
-				self.unset_line_changed ();
-				self.stats.line_iterations += 1;
+		for (row_index, col_index) in self.grid.unknown_cells () {
 
-				continue;
+			let cell = grids.iter ().fold (
+				Cell::ERROR,
+				|cell, grid| cell.union (grid [(row_index, col_index)]),
+			);
 
+			if cell != self.grid [(row_index, col_index)] {
+				self.probed_cells.push ((row_index, col_index, cell));
 			}
 
-			if self.grid.is_solved () {
-				self.stats.grid_iterations += 1;
-				self.state = State::Complete;
-				return Some (GridSolverEvent::SolvedGrid);
-			}
+		}
 
-			while ! self.get_line_changed ()
-			|| self.get_line ().all (Cell::is_solved) {
-				self.advance ();
-			}
+		// pop from the end, so reverse to yield cells in order
 
-			if self.index_changed {
+		self.probed_cells.reverse ();
 
-				self.index_changed = false;
+		Ok (())
 
-				return Some (
-					if ! self.vertical {
-						GridSolverEvent::StartRow (self.index)
-					} else {
-						GridSolverEvent::StartCol (self.index)
-					}
-				);
+	}
 
-			}
+	fn probe_branch (
+		& mut self,
+		row_index: LineSize,
+		col_index: LineSize,
+		cell: Cell,
+	) -> Result <Grid, LineRef> {
 
-			self.state = State::Solving (0);
+		let mut grid = self.grid.clone ();
+		grid [(row_index, col_index)] = cell;
 
-			let mut line_solver = Default::default ();
-			mem::swap (& mut line_solver, & mut self.line_solver);
+		let mut changed_rows = vec! [false; grid.num_rows () as usize];
+		let mut changed_cols = vec! [false; grid.num_cols () as usize];
+		changed_rows [row_index as usize] = true;
+		changed_cols [col_index as usize] = true;
 
-			self.line_solver = match line_solver.into_new (
-				self.get_line (),
-				self.get_clues (),
-			) {
-				Ok (val) => val,
-				Err (_) => panic! (),
-			};
+		let result = propagate (
+			& mut grid,
+			& self.clues,
+			& mut changed_rows,
+			& mut changed_cols,
+			& mut self.line_solver,
+			self.colour_line_solver.as_mut (),
+			& mut self.stats,
+		);
 
-		}
+		self.update_cache_stats ();
 
-	}
+		result.map (|()| grid)
 
-	pub fn end (self) -> (Grid, GridSolverStats) {
-		(self.grid, self.stats)
 	}
 
-}
+	fn next_probe (& mut self) -> Option <GridSolverEvent> {
 
-impl GridSolverStats {
+		// apply cells found by the last probe, one per event
 
-	pub fn new () -> GridSolverStats {
+		if let Some ((row_index, col_index, cell)) = self.probed_cells.pop () {
 
-		GridSolverStats {
-			grid_iterations: 0,
-			line_iterations: 0,
-		}
+			self.set_grid_cell (row_index, col_index, cell);
+			self.stats.probed_cells += 1;
 
-	}
+			if self.probed_cells.is_empty () {
+				self.state = State::Scanning;
+			}
 
-}
+			return Some (GridSolverEvent::ProbedCell (row_index, col_index, cell));
+
+		}
 
+		let position = match self.state {
+			State::Probing (row_index, col_index) => (row_index, col_index),
+			_ => panic! (),
+		};
+
+		let (row_index, col_index) = match self.grid.unknown_cells ().find (
+			|& cell_position| cell_position >= position,
+		) {
+			Some (val) => val,
+			None => {
+				self.state = State::Scanning;
+				return None;
+			},
+		};
+
+		if let Err (line_ref) = self.probe (row_index, col_index) {
+
+			if let Some (event) = self.backtrack () {
+				return Some (event);
+			}
+
+			self.state = State::Contradiction;
+
+			return Some (GridSolverEvent::Contradiction (
+				line_ref,
+				self.get_clues_line (line_ref).clone (),
+			));
+
+		}
+
+		// the cells found all rely on every cell known before probing
+
+		if let Some (ref mut proof) = self.proof {
+			if ! self.probed_cells.is_empty () {
+				self.proof_step = proof.add_step (ProofStep::Probe (
+					row_index,
+					col_index,
+					known_cells (& self.grid, self.clues.num_colours ()),
+				));
+			}
+		}
+
+		if self.probed_cells.is_empty () {
+			self.state = if col_index + 1 < self.grid.num_cols () {
+				State::Probing (row_index, col_index + 1)
+			} else {
+				State::Probing (row_index + 1, 0)
+			};
+		}
+
+		Some (GridSolverEvent::Probe (row_index, col_index))
+
+	}
+
+	fn guess (& mut self) -> GridSolverEvent {
+
+		let (row_index, col_index) = self.grid.unknown_cells ().next ().unwrap ();
+
+		self.guesses.push (GuessFrame {
+			grid: self.grid.clone (),
+			row_index: row_index,
+			col_index: col_index,
+		});
+
+		let cell = cell_values (self.grid [(row_index, col_index)], self.clues.num_colours ()) [0];
+
+		if let Some (ref mut proof) = self.proof {
+			self.proof_step = proof.add_step (ProofStep::Guess (row_index, col_index));
+		}
+
+		self.set_grid_cell (row_index, col_index, cell);
+
+		self.stats.guesses += 1;
+		self.stats.max_depth = usize::max (
+			self.stats.max_depth,
+			self.guesses.len (),
+		);
+
+		GridSolverEvent::Guess (row_index, col_index, cell)
+
+	}
+
+	fn backtrack (& mut self) -> Option <GridSolverEvent> {
+
+		let frame = self.guesses.pop () ?;
+
+		// rule out the value which was guessed, leaving whatever else the cell
+		// could be, which for colour puzzles may need guessing again
+
+		let guessed_cell = frame.grid [(frame.row_index, frame.col_index)];
+		let guess = cell_values (guessed_cell, self.clues.num_colours ()) [0];
+		let cell = guessed_cell.without (guess);
+
+		// only the guessed cell has changed since the guess was made, because
+		// guesses are only made once there are no changed lines left
+
+		self.grid = frame.grid;
+		self.changed_rows.iter_mut ().for_each (|changed| * changed = false);
+		self.changed_cols.iter_mut ().for_each (|changed| * changed = false);
+		self.row_changes.iter_mut ().for_each (|changes| * changes = 0);
+		self.col_changes.iter_mut ().for_each (|changes| * changes = 0);
+		self.probed_cells.clear ();
+		self.batch.clear ();
+		self.state = State::Scanning;
+
+		// what was learned after the guess goes with it, and ruling the guess
+		// out relies on the cells known when it was made
+
+		if let Some (ref mut proof) = self.proof {
+
+			if let Some (guess_step) = proof.last_guess (frame.row_index, frame.col_index) {
+				proof.rewind (guess_step);
+			}
+
+			self.proof_step = proof.add_step (ProofStep::Backtrack (
+				frame.row_index,
+				frame.col_index,
+				known_cells (& self.grid, self.clues.num_colours ()),
+			));
+
+		}
+
+		self.set_grid_cell (frame.row_index, frame.col_index, cell);
+
+		self.stats.backtracks += 1;
+
+		Some (GridSolverEvent::Backtrack (
+			frame.row_index,
+			frame.col_index,
+			cell,
+		))
+
+	}
+
+	pub fn resume (& mut self) -> Option <GridSolverEvent> {
+
+		if ! self.is_solved () {
+			return None;
+		}
+
+		self.backtrack ()
+
+	}
+
+	fn is_complete (& self) -> bool {
+		matches! (self.state, State::Complete | State::Contradiction | State::Stuck)
+	}
+
+	pub fn is_solved (& self) -> bool {
+		matches! (self.state, State::Complete)
+	}
+
+	pub fn is_contradiction (& self) -> bool {
+		matches! (self.state, State::Contradiction)
+	}
+
+	pub fn is_stuck (& self) -> bool {
+		matches! (self.state, State::Stuck)
+	}
+
+	fn get_line_ref (& self) -> LineRef {
+
+		if ! self.vertical {
+			LineRef::Row (self.index)
+		} else {
+			LineRef::Col (self.index)
+		}
+
+	}
+
+	fn is_solving (& self) -> bool {
+		matches! (self.state, State::Solving (..))
+	}
+
+	pub (super) fn is_probing (& self) -> bool {
+		matches! (self.state, State::Probing (..))
+	}
+
+	fn next_cell (& mut self) -> Option <(LineSize, Cell)> {
+
+		match self.state {
+
+			State::Solving (ref mut cell_index) => {
+
+				if * cell_index < self.solved_line.len () {
+
+					let result = (* cell_index, self.solved_line [* cell_index]);
+					* cell_index += 1;
+					Some (result)
+
+				} else {
+					None
+				}
+
+			},
+
+			_ => panic! (),
+
+		}
+
+	}
+
+	// not an iterator, since callers interleave this with access to the grid
+
+	#[ allow (clippy::should_implement_trait) ]
+	pub fn next (
+		& mut self,
+	) -> Option <GridSolverEvent> {
+
+		if self.is_complete () {
+			return None;
+		}
+
+		loop {
+
+			if self.is_probing () {
+
+				if let Some (event) = self.next_probe () {
+					return Some (event);
+				}
+
+				continue;
+
+			}
+
+			if self.is_solving () {
+
+				if let Some ((cell_index, cell)) = self.next_cell () {
+
+					if cell == self.get_cell (cell_index) {
+						continue;
+					}
+
+					self.set_cell (cell_index, cell);
+
+					// colour cells may only be narrowed down, and aren't solved yet
+
+					if ! cell.is_solved () {
+						continue;
+					}
+
+					return Some (
+						if ! self.vertical {
+							GridSolverEvent::SolvedCell (self.index, cell_index)
+						} else {
+							GridSolverEvent::SolvedCell (cell_index, self.index)
+						}
+					);
+
+				}
+
+				self.state = State::Scanning;
+				self.unset_line_changed ();
+				self.stats.line_iterations += 1;
+
+				continue;
+
+			}
+
+			if ! self.find_changed_line () {
+
+				if self.grid.is_solved () {
+					self.stats.grid_iterations += 1;
+					self.state = State::Complete;
+					return Some (GridSolverEvent::SolvedGrid);
+				}
+
+				if self.config.probing && ! self.probed {
+					self.probed = true;
+					self.state = State::Probing (0, 0);
+					continue;
+				}
+
+				if self.config.backtracking {
+					return Some (self.guess ());
+				}
+
+				self.state = State::Stuck;
+				return Some (GridSolverEvent::Stuck);
+
+			}
+
+			if self.index_changed {
+
+				self.index_changed = false;
+
+				return Some (
+					if ! self.vertical {
+						GridSolverEvent::StartRow (self.index)
+					} else {
+						GridSolverEvent::StartCol (self.index)
+					}
+				);
+
+			}
+
+			if ! self.solve_current_line () {
+
+				self.stats.line_iterations += 1;
+
+				if let Some (event) = self.backtrack () {
+					return Some (event);
+				}
+
+				self.state = State::Contradiction;
+
+				return Some (GridSolverEvent::Contradiction (
+					self.get_line_ref (),
+					self.get_clues ().clone (),
+				));
+
+			}
+
+			self.state = State::Solving (0);
+
+		}
+
+	}
+
+	pub fn end (self) -> (Grid, GridSolverStats) {
+		(self.grid, self.stats)
+	}
+
+}
+
+impl Default for GridSolverConfig {
+
+	fn default () -> GridSolverConfig {
+
+		GridSolverConfig {
+			backtracking: false,
+			probing: false,
+			scan_order: ScanOrder::RoundRobin,
+			line_cache_size: 4096,
+			threads: 1,
+			record_proof: false,
+		}
+
+	}
+
+}
+
+fn clues_line_slack (
+	clues_line: & CluesLine,
+	line_size: LineSize,
+) -> LineSize {
+
+	let min_size = clues_line.iter ().sum::<LineSize> ()
+		+ (clues_line.len () as LineSize).saturating_sub (1);
+
+	line_size.saturating_sub (min_size)
+
+}
+
+impl fmt::Display for LineRef {
+
+	fn fmt (& self, formatter: & mut fmt::Formatter <'_>) -> fmt::Result {
+
+		match * self {
+			LineRef::Row (index) => write! (formatter, "row {}", index),
+			LineRef::Col (index) => write! (formatter, "col {}", index),
+		}
+
+	}
+
+}
+
+impl GridSolverSnapshot {
+
+	pub fn grid (& self) -> & Grid {
+		& self.grid
+	}
+
+	pub fn stats (& self) -> & GridSolverStats {
+		& self.stats
+	}
+
+	pub fn backtracking (& self) -> bool {
+		self.backtracking
+	}
+
+	pub fn probing (& self) -> bool {
+		self.probing
+	}
+
+}
+
+impl GridSolverStats {
+
+	pub fn new () -> GridSolverStats {
+
+		Default::default ()
+
+	}
+
+}
+
+// every cell which is at least partly known, which is what probes and guesses
+// rely on
+
+fn known_cells (grid: & Grid, num_colours: u8) -> Vec <(LineSize, LineSize)> {
+
+	let unknown = Cell::unknown (num_colours);
+
+	(0 .. grid.num_rows ()).flat_map (
+		|row_index| (0 .. grid.num_cols ()).map (move |col_index| (row_index, col_index)),
+	).filter (
+		|& position| grid [position] != unknown,
+	).collect ()
+
+}
+
+// the values a cell could be, each colour in turn and then empty, which for
+// monochrome puzzles is filled then empty
+
+fn cell_values (cell: Cell, num_colours: u8) -> Vec <Cell> {
+
+	(1 ..= num_colours).filter (
+		|& index| cell.can_colour (index),
+	).map (Cell::colour).chain (
+		Some (Cell::EMPTY).filter (|_| cell.can_empty ()),
+	).collect ()
+
+}
+
+
+#[ cfg (test) ]
+mod tests {
+
+	use super::*;
+
+	#[ test ]
+	fn test_grid_solver_solved () {
+
+		let clues = load_clues ("rows\n1 1\n3\ncols\n2\n1\n2\n");
+		let grid = unknown_grid (& clues);
+		let mut grid_solver = GridSolver::new (grid, clues).unwrap ();
+
+		finish (& mut grid_solver);
+
+		assert! (grid_solver.grid ().is_solved ());
+		assert! (! grid_solver.is_contradiction ());
+
+	}
+
+	#[ test ]
+	fn test_grid_solver_contradiction () {
+
+		let clues = load_clues ("rows\n2\ncols\n1\n1\n1\n");
+		let mut grid = unknown_grid (& clues);
+		grid [(0, 1)] = Cell::EMPTY;
+		let mut grid_solver = GridSolver::new (grid, clues).unwrap ();
+
+		assert! (matches! (
+			grid_solver.next (),
+			Some (GridSolverEvent::StartRow (0)),
+		));
+
+		match grid_solver.next () {
+			Some (GridSolverEvent::Contradiction (line_ref, clues_line)) => {
+				assert_eq! (line_ref, LineRef::Row (0));
+				assert_eq! (clues_line, vec! [ 2 ]);
+			},
+			event => panic! ("Unexpected event: {:?}", event),
+		}
+
+		assert! (grid_solver.next ().is_none ());
+		assert! (grid_solver.is_contradiction ());
+
+	}
+
+	#[ test ]
+	fn test_grid_solver_stuck () {
+
+		let clues = load_clues ("rows\n1\n1\ncols\n1\n1\n");
+		let grid = unknown_grid (& clues);
+		let mut grid_solver = GridSolver::new (grid, clues).unwrap ();
+
+		let mut last_event = None;
+		while let Some (event) = grid_solver.next () {
+			last_event = Some (event);
+		}
+
+		assert! (matches! (last_event, Some (GridSolverEvent::Stuck)));
+		assert! (grid_solver.is_stuck ());
+		assert! (! grid_solver.grid ().is_solved ());
+
+	}
+
+	#[ test ]
+	fn test_grid_solver_probing () {
+
+		let source = "rows\n1\n3\n1 1\n1 1\n1 1\ncols\n1 2\n1 1\n1\n1\n3\n";
+
+		let clues = load_clues (source);
+		let grid = unknown_grid (& clues);
+		let mut grid_solver = GridSolver::with_config (grid, clues, GridSolverConfig {
+			probing: true,
+			.. Default::default ()
+		}).unwrap ();
+
+		let mut probed_cells = 0;
+		while let Some (event) = grid_solver.next () {
+			if let GridSolverEvent::ProbedCell (..) = event {
+				probed_cells += 1;
+			}
+		}
+
+		assert! (grid_solver.is_solved ());
+		assert! (grid_solver.grid ().is_solved ());
+		assert! (probed_cells > 0);
+		assert_eq! (grid_solver.stats ().probed_cells, probed_cells);
+		assert_eq! (grid_solver.stats ().guesses, 0);
+
+	}
+
+	#[ derive (Default) ]
+	struct CountingLineSolver {
+		inner: LineSolver,
+		count: usize,
+	}
+
+	impl LineSolve for CountingLineSolver {
+
+		fn solve_line <
+			LineIter: IntoIterator <Item = Cell>,
+		> (
+			& mut self,
+			line_iter: LineIter,
+			clues_line: & CluesLine,
+		) -> Option <& Line> {
+			self.count += 1;
+			self.inner.solve_line (line_iter, clues_line)
+		}
+
+	}
+
+	#[ test ]
+	fn test_grid_solver_line_solver () {
+
+		let clues = load_clues ("rows\n1 1\n3\ncols\n2\n1\n2\n");
+		let grid = unknown_grid (& clues);
+		let mut grid_solver = GridSolver::with_line_solver (
+			grid,
+			clues,
+			GridSolverConfig {
+				line_cache_size: 0,
+				.. Default::default ()
+			},
+			CountingLineSolver::default (),
+		).unwrap ();
+
+		finish (& mut grid_solver);
+
+		assert! (grid_solver.is_solved ());
+		assert_eq! (
+			grid_solver.line_solver ().count,
+			grid_solver.stats ().line_iterations,
+		);
+
+	}
+
+	#[ test ]
+	fn test_grid_solver_priority () {
+
+		for name in [ "camel", "heart", "wikipedia" ] {
+
+			let clues = load_sample (name);
+
+			let grid = unknown_grid (& clues);
+
+			let mut round_robin_solver = GridSolver::new (grid.clone (), clues.clone ()).unwrap ();
+			finish (& mut round_robin_solver);
+
+			let mut priority_solver = GridSolver::with_config (grid, clues, GridSolverConfig {
+				scan_order: ScanOrder::Priority,
+				.. Default::default ()
+			}).unwrap ();
+			finish (& mut priority_solver);
+
+			assert! (priority_solver.is_solved (), "{}", name);
+			assert_eq! (priority_solver.grid (), round_robin_solver.grid (), "{}", name);
+
+		}
+
+	}
+
+	#[ test ]
+	fn test_grid_solver_threads () {
+
+		for name in [ "camel", "heart", "wikipedia" ] {
+
+			let clues = load_sample (name);
+
+			let grid = unknown_grid (& clues);
+
+			let mut sequential_solver = GridSolver::new (grid.clone (), clues.clone ()).unwrap ();
+			let mut sequential_events = Vec::new ();
+			while let Some (event) = sequential_solver.next () {
+				sequential_events.push (format! ("{:?}", event));
+			}
+
+			let mut parallel_solver = GridSolver::with_config (grid, clues, GridSolverConfig {
+				threads: 4,
+				.. Default::default ()
+			}).unwrap ();
+			let mut parallel_events = Vec::new ();
+			while let Some (event) = parallel_solver.next () {
+				parallel_events.push (format! ("{:?}", event));
+			}
+
+			assert! (parallel_solver.is_solved (), "{}", name);
+			assert_eq! (parallel_events, sequential_events, "{}", name);
+
+			// the workers' solves go through the shared cache as well
+
+			assert_eq! (
+				parallel_solver.stats ().cache_hits + parallel_solver.stats ().cache_misses,
+				sequential_solver.stats ().cache_hits + sequential_solver.stats ().cache_misses,
+				"{}", name,
+			);
+
+		}
+
+	}
+
+	#[ test ]
+	fn test_grid_solver_snapshot () {
+
+		let clues = load_sample ("wikipedia");
+
+		let mut grid_solver = GridSolver::new (
+			unknown_grid (& clues),
+			clues,
+		).unwrap ();
+
+		// take a snapshot part way through solving a line
+
+		for _ in 0 .. 50 {
+			grid_solver.next ().unwrap ();
+		}
+
+		let snapshot = grid_solver.snapshot ();
+
+		let mut events = Vec::new ();
+		while let Some (event) = grid_solver.next () {
+			events.push (format! ("{:?}", event));
+		}
+
+		let solved_grid = grid_solver.grid ().clone ();
+		let line_iterations = grid_solver.stats ().line_iterations;
+		let stats = * grid_solver.stats ();
+		assert! (stats.cache_misses > snapshot.stats ().cache_misses);
+
+		// restoring it replays exactly the same events
+
+		grid_solver.restore (snapshot.clone ());
+
+		assert_eq! (grid_solver.grid (), snapshot.grid ());
+		assert_eq! (grid_solver.stats ().cache_hits, snapshot.stats ().cache_hits);
+		assert_eq! (grid_solver.stats ().cache_misses, snapshot.stats ().cache_misses);
+		assert! (! grid_solver.is_solved ());
+
+		let mut restored_events = Vec::new ();
+		while let Some (event) = grid_solver.next () {
+			restored_events.push (format! ("{:?}", event));
+		}
+
+		assert_eq! (restored_events, events);
+		assert_eq! (grid_solver.grid (), & solved_grid);
+		assert_eq! (grid_solver.stats ().line_iterations, line_iterations);
+
+		// the cache is warm now, but it sees the same number of lookups
+
+		assert_eq! (
+			grid_solver.stats ().cache_hits + grid_solver.stats ().cache_misses,
+			stats.cache_hits + stats.cache_misses,
+		);
+
+		assert! (grid_solver.stats ().cache_hits > stats.cache_hits);
+
+	}
+
+	fn backtracking_solver (source: & str) -> GridSolver {
+
+		let clues = load_clues (source);
+		let grid = unknown_grid (& clues);
+
+		GridSolver::with_config (grid, clues, GridSolverConfig {
+			backtracking: true,
+			.. Default::default ()
+		}).unwrap ()
+
+	}
+
+	#[ test ]
+	fn test_grid_solver_guess () {
+
+		let mut grid_solver = backtracking_solver (
+			"rows\n1\n1\ncols\n1\n1\n",
+		);
+
+		let mut guessed = false;
+		while let Some (event) = grid_solver.next () {
+			if let GridSolverEvent::Guess (0, 0, Cell::FILLED) = event {
+				guessed = true;
+			}
+		}
+
+		assert! (guessed);
+		assert! (grid_solver.grid ().is_solved ());
+		assert_eq! (grid_solver.grid () [(0, 0)], Cell::FILLED);
+		assert_eq! (grid_solver.stats ().guesses, 1);
+		assert_eq! (grid_solver.stats ().backtracks, 0);
+
+	}
+
+	#[ test ]
+	fn test_grid_solver_backtrack () {
+
+		let mut grid_solver = backtracking_solver (
+			"rows\n2\n1 1\n1\n1\ncols\n2\n2\n1\n1\n",
+		);
+
+		let mut backtracked = false;
+		while let Some (event) = grid_solver.next () {
+			if let GridSolverEvent::Backtrack (_, _, Cell::EMPTY) = event {
+				backtracked = true;
+			}
+		}
+
+		assert! (backtracked);
+		assert! (grid_solver.grid ().is_solved ());
+		assert_eq! (grid_solver.depth (), 0);
+		assert_eq! (grid_solver.stats ().backtracks, 1);
+
+	}
+
+	// the clues alone have two solutions, the given cell picks one of them
+
+	#[ test ]
+	fn test_grid_solver_given_cells () {
+
+		let clues = load_clues ("rows\n1\n1\ncols\n1\n1\n");
+		let grid = Grid::load (& mut "#-\n--\n".as_bytes ()).unwrap ();
+
+		let mut grid_solver = GridSolver::new (grid, clues).unwrap ();
+		finish (& mut grid_solver);
+
+		assert! (grid_solver.is_solved ());
+		assert_eq! (grid_solver.grid (), & Grid::load (& mut "# \n #\n".as_bytes ()).unwrap ());
+
+	}
+
+	// the given row is already solved, but wrong, and the cols alone would
+	// finish the grid without noticing
+
+	#[ test ]
+	fn test_grid_solver_solved_line_contradiction () {
+
+		let clues = load_clues ("rows\n1\n1\ncols\n1\n1\n");
+		let grid = Grid::load (& mut "##\n--\n".as_bytes ()).unwrap ();
+
+		let mut grid_solver = GridSolver::new (grid, clues).unwrap ();
+		finish (& mut grid_solver);
+
+		assert! (grid_solver.is_contradiction ());
+
+	}
+
+	#[ test ]
+	fn test_grid_solver_colours () {
+
+		let clues = load_sample ("colours");
+
+		assert! (clues.is_consistent ());
+
+		let mut grid_solver = GridSolver::new (
+			unknown_grid (& clues),
+			clues,
+		).unwrap ();
+
+		finish (& mut grid_solver);
+
+		assert! (grid_solver.is_solved ());
+
+		let rows: Vec <String> = (0 .. 5).map (
+			|row_index| format! ("{:?}", grid_solver.grid ().row (row_index).collect::<LineBuf> ()),
+		).collect ();
+
+		assert_eq! (rows, vec! [ "[##333]", "[##3  ]", "[ 222 ]", "[ 2 2 ]", "[22222]" ]);
+
+	}
+
+	// line solving gets nowhere with these, so they need probing and guessing
+
+	#[ test ]
+	fn test_grid_solver_colours_probing () {
+
+		let clues = load_clues (
+			"colours\nr ff0000\nb 0000ff\nrows\n1r\n2b\n1b 1b\ncols\n1b\n1b\n1b\n1r 1b\n",
+		);
+
+		let mut grid_solver = GridSolver::with_config (
+			unknown_grid (& clues),
+			clues,
+			GridSolverConfig {
+				probing: true,
+				.. Default::default ()
+			},
+		).unwrap ();
+
+		finish (& mut grid_solver);
+
+		assert! (grid_solver.is_solved ());
+		assert! (grid_solver.stats ().probed_cells > 0);
+		assert_eq! (grid_solver.stats ().guesses, 0);
+
+		let rows: Vec <String> = (0 .. 3).map (
+			|row_index| format! ("{:?}", grid_solver.grid ().row (row_index).collect::<LineBuf> ()),
+		).collect ();
+
+		assert_eq! (rows, vec! [ "[   #]", "[ 22 ]", "[2  2]" ]);
+
+	}
+
+	#[ test ]
+	fn test_grid_solver_colours_backtrack () {
+
+		let clues = load_clues (
+			"colours\nr ff0000\nb 0000ff\nrows\n1b 1r\n1b 1r\ncols\n1b\n1b\n2r\n",
+		);
+
+		let mut grid_solver = GridSolver::with_config (
+			unknown_grid (& clues),
+			clues,
+			GridSolverConfig {
+				backtracking: true,
+				.. Default::default ()
+			},
+		).unwrap ();
+
+		let mut guessed = false;
+		while let Some (event) = grid_solver.next () {
+			if let GridSolverEvent::Guess (0, 0, cell) = event {
+				guessed = cell == Cell::colour (2);
+			}
+		}
+
+		assert! (guessed);
+		assert! (grid_solver.is_solved ());
+		assert_eq! (grid_solver.grid () [(0, 0)], Cell::colour (2));
+
+		// the other solution has the guessed cell empty
+
+		assert! (matches! (grid_solver.resume (), Some (GridSolverEvent::Backtrack (0, 0, Cell::EMPTY))));
+		finish (& mut grid_solver);
+
+		assert! (grid_solver.is_solved ());
+		assert_eq! (grid_solver.grid () [(0, 1)], Cell::colour (2));
+
+	}
+
+	#[ test ]
+	fn test_grid_solver_mismatch () {
+
+		let clues = load_sample ("colours");
+
+		let error = GridSolver::new (
+			Grid::new (clues.num_rows (), clues.num_cols ()),
+			clues.clone (),
+		).err ().unwrap ();
+
+		assert_eq! (error, GridSolverError::Colours { grid: 1, clues: 3 });
+		assert_eq! (error.to_string (), "grid has 1 colours but the clues have 3");
+
+		let error = GridSolver::new (
+			Grid::with_colours (clues.num_rows () + 1, clues.num_cols (), 3),
+			clues.clone (),
+		).err ().unwrap ();
+
+		assert_eq! (
+			error,
+			GridSolverError::Size {
+				grid: (clues.num_rows () + 1, clues.num_cols ()),
+				clues: (clues.num_rows (), clues.num_cols ()),
+			},
+		);
+
+	}
+
+}
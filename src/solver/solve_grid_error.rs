@@ -0,0 +1,51 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::data::*;
+
+// a starting grid which doesn't fit the clues it is to be solved against
+
+#[ derive (Clone, Copy, Debug, Eq, PartialEq) ]
+pub enum GridSolverError {
+
+	Size {
+		grid: (LineSize, LineSize),
+		clues: (LineSize, LineSize),
+	},
+
+	// unknown cells must allow exactly the colours in the clues
+
+	Colours {
+		grid: u8,
+		clues: u8,
+	},
+
+}
+
+impl fmt::Display for GridSolverError {
+
+	fn fmt (& self, formatter: & mut fmt::Formatter <'_>) -> fmt::Result {
+
+		match * self {
+			GridSolverError::Size { grid, clues } => write! (
+				formatter,
+				"grid is {}x{} but the clues are {}x{}",
+				grid.0,
+				grid.1,
+				clues.0,
+				clues.1,
+			),
+			GridSolverError::Colours { grid, clues } => write! (
+				formatter,
+				"grid has {} colours but the clues have {}",
+				grid,
+				clues,
+			),
+		}
+
+	}
+
+}
+
+impl Error for GridSolverError {
+}
@@ -0,0 +1,36 @@
+use std::path::Path;
+
+use crate::data::*;
+use crate::solver::*;
+
+// shared by the tests, most of which load some clues or samples and solve them
+
+pub fn load_clues (source: & str) -> Clues {
+	Clues::load (& mut source.as_bytes ()).unwrap ()
+}
+
+pub fn load_sample (name: & str) -> Clues {
+	Clues::load_file (
+		& Path::new (env! ("CARGO_MANIFEST_DIR")).join ("samples").join (name),
+	).unwrap ()
+}
+
+pub fn unknown_grid (clues: & Clues) -> Grid {
+	Grid::with_colours (clues.num_rows (), clues.num_cols (), clues.num_colours ())
+}
+
+pub fn finish <Solver: LineSolve + Default + Send> (grid_solver: & mut GridSolver <Solver>) {
+	while grid_solver.next ().is_some () { }
+}
+
+pub fn solve_sample (name: & str) -> GridSolver {
+
+	let clues = load_sample (name);
+
+	let mut grid_solver = GridSolver::new (unknown_grid (& clues), clues).unwrap ();
+
+	finish (& mut grid_solver);
+
+	grid_solver
+
+}